@@ -3,9 +3,11 @@ use log::{debug, error};
 use serde::{Deserialize, Serialize};
 use crate::actor::CompoundActorReference;
 use crate::actor::ActorReference::Url;
+use crate::content::deserialize_lenient;
 use crate::context::Context;
 use crate::entity::{entity_type_from, EntityType};
 use crate::object::{Object, ObjectReference, ObjectTrait};
+use crate::tag::TagReference;
 
 /// Activity object.
 /// See: <https://www.w3.org/TR/activitystreams-core/#activities>
@@ -20,6 +22,44 @@ pub struct Activity {
 
     /// Actor reference.
     pub actor: CompoundActorReference,
+
+    /// Community activity is addressed to, threadiverse software,
+    /// e.g. Lemmy, sets it to Group actor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<CompoundActorReference>,
+
+    /// Content of activity, Pleroma and Misskey put emoji reaction here.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+
+    /// Emoji reaction as Misskey sends it with `Like`.
+    #[serde(rename = "_misskey_reaction")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub misskey_reaction: Option<String>,
+
+    /// Tags of activity, e.g. custom `Emoji` used as reaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<TagReference>,
+
+    /// Approval of interaction by author of interacted object,
+    /// older GoToSocial's way, superseded by `likeAuthorization`
+    /// and `announceAuthorization`.
+    #[serde(rename = "approvedBy")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<url::Url>,
+
+    /// Reference to `LikeAuthorization` of this `Like`.
+    #[serde(rename = "likeAuthorization")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub like_authorization: Option<url::Url>,
+
+    /// Reference to `AnnounceAuthorization` of this `Announce`.
+    #[serde(rename = "announceAuthorization")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announce_authorization: Option<url::Url>,
 }
 
 impl ObjectTrait for Activity {
@@ -52,6 +92,13 @@ impl Activity {
             object_entity: Object::new_with_entity_type(activity_type, id),
             object,
             actor: CompoundActorReference::Reference(Url(actor)),
+            audience: None,
+            content: None,
+            misskey_reaction: None,
+            tag: None,
+            approved_by: None,
+            like_authorization: None,
+            announce_authorization: None,
         })
     }
 
@@ -69,6 +116,13 @@ impl Activity {
             object_entity,
             object,
             actor: CompoundActorReference::Reference(Url(actor)),
+            audience: None,
+            content: None,
+            misskey_reaction: None,
+            tag: None,
+            approved_by: None,
+            like_authorization: None,
+            announce_authorization: None,
         })
     }
 
@@ -7,7 +7,9 @@ use crate::attachment::AttachmentReference;
 use crate::context::Context;
 use crate::discoverable::{AllowReason, DenyReason, Discoverable};
 use crate::entity::EntityType;
+use crate::id::{deserialize_id_url, deserialize_optional_id_url, ids_match, ObjectId};
 use crate::image::ImageReference;
+use crate::language::{best_localized, normalize_language_map};
 use crate::object::{Object, ObjectTrait};
 use crate::tag::TagReference;
 
@@ -40,18 +42,22 @@ pub struct Actor {
     pub object_entity: Object,
 
     /// Link to this actor's inbox.
+    #[serde(deserialize_with = "deserialize_id_url")]
     pub inbox: url::Url,
 
     /// URL of this actor's outbox.
+    #[serde(default, deserialize_with = "deserialize_optional_id_url")]
     pub outbox: Option<url::Url>,
 
     /// Link to followers collection.
     /// SHOULD have as Actor
+    #[serde(default, deserialize_with = "deserialize_optional_id_url")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub followers: Option<url::Url>,
 
     /// Link to collection of actors this one follows.
     /// SHOULD have as Actor
+    #[serde(default, deserialize_with = "deserialize_optional_id_url")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub following: Option<url::Url>,
 
@@ -77,13 +83,27 @@ pub struct Actor {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name_map: Option<HashMap<String, String>>,
 
-    /// Short summary of what this actor about, could have summaryMap as well,
-    /// though ignored in this crate
+    /// Short summary of what this actor about, see also `summary_map`.
     /// MAY have as ActivityStream inheritance.
     /// See: <https://www.w3.org/TR/activitystreams-vocabulary/#dfn-summary>
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
 
+    /// Optional map to represent summary in multiple languages.
+    #[serde(rename = "summaryMap")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary_map: Option<HashMap<String, String>>,
+
+    /// Link to collection of community moderators, it is set for
+    /// `Group` actors by threadiverse software, e.g. Lemmy.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moderators: Option<url::Url>,
+
+    /// Flag to indicate only moderators could post to community.
+    #[serde(rename = "postingRestrictedToMods")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub posting_restricted_to_mods: Option<bool>,
+
     /// Optional image/icon used as Actor's avatar.
     /// See: <https://www.w3.org/TR/activitystreams-vocabulary/#dfn-icon>
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -136,6 +156,19 @@ impl ObjectTrait for Actor {
     }
 }
 
+/// Picks the best value from language map with non-normalized keys.
+fn localized<'a>(
+    map: Option<&'a HashMap<String, String>>,
+    accept_language: &str,
+) -> Option<&'a String> {
+    let map = normalize_language_map(
+        map?.iter().map(|(k, v)| (k.as_str(), v))
+    );
+
+    best_localized(&map, accept_language)
+        .map(|(_, value)| *value)
+}
+
 impl Actor {
     /// This method returns Actor's `name` property inherited from Object,
     /// if any.
@@ -143,6 +176,22 @@ impl Actor {
         &self.object_entity.name
     }
 
+    /// Returns Actor's name in language that suits `accept_language` best,
+    /// falls back to `name` property if `nameMap` has no suitable value.
+    /// See [crate::language::best_localized()].
+    pub fn localized_name(&self, accept_language: &str) -> Option<&String> {
+        localized(self.name_map.as_ref(), accept_language)
+            .or(self.object_entity.name.as_ref())
+    }
+
+    /// Returns Actor's summary in language that suits `accept_language`
+    /// best, falls back to `summary` property if `summaryMap` has no
+    /// suitable value.
+    pub fn localized_summary(&self, accept_language: &str) -> Option<&String> {
+        localized(self.summary_map.as_ref(), accept_language)
+            .or(self.summary.as_ref())
+    }
+
     /// This method checks if Actor object has security context and
     /// public key related property. Returns reference to self,
     /// wrapped into [Option]. It is empty [Option] if validation did fail.
@@ -319,7 +368,8 @@ impl Actor {
 
     /// Returns true if actor ID or addressee matches `pattern`.
     pub fn matches(&self, pattern: &str) -> bool {
-        if self.object_entity.id.as_str().contains(pattern) {
+        if self.object_entity.id.as_str().contains(pattern) ||
+            ids_match(self.object_entity.id.as_str(), pattern) {
             return true;
         }
 
@@ -396,16 +446,26 @@ impl ActorReference {
         }
     }
 
+    /// Returns true if this reference is public stream, either
+    /// [PUBLIC_ADDRESSEE], its `as:Public` compact form or `Public`.
+    pub fn is_public(&self) -> bool {
+        matches!(self.id().as_str(), PUBLIC_ADDRESSEE | "as:Public")
+    }
+
     /// Returns true if any actor reference matches `pattern` string.
+    /// Portable IDs are matched in canonical form too.
     pub fn matches(&self, pattern: &str) -> bool {
         match self {
             ActorReference::Actor(actor) => actor.matches(pattern),
             ActorReference::BasicData(object) => object.matches(pattern),
-            ActorReference::Url(url) => url.as_str().contains(pattern),
+            ActorReference::Url(url) => url.as_str().contains(pattern) ||
+                ids_match(url.as_str(), pattern),
 
             ActorReference::CatchAll(value) => match value.as_str() {
                 "Public" => PUBLIC_ADDRESSEE.contains(pattern),
-                _ => value.contains(pattern) || NONE_ADDRESSEE.contains(pattern)
+                _ => value.contains(pattern) ||
+                    ids_match(value, pattern) ||
+                    NONE_ADDRESSEE.contains(pattern)
             }
         }
     }
@@ -425,40 +485,55 @@ pub enum CompoundActorReference {
 
 impl CompoundActorReference {
     /// Returns ID of actor.
-    /// In case of multiple actors returns ID of first Person-like entity
-    /// in list or ID of first actor in list if there is no any
-    /// Person-like entity.
+    ///
+    /// In case of multiple actors this is a heuristic that does not know
+    /// audience: the first Person-like actor is preferred, then the first
+    /// non-Group actor, then just the first actor. So community Group listed
+    /// next to another Group could be returned. For authors of content use
+    /// [crate::content::Content::author_id()] or
+    /// [CompoundActorReference::author_id()] with audience.
     pub fn id(&self) -> Option<&url::Url> {
+        self.author_id(None)
+    }
+
+    /// Returns ID of author.
+    ///
+    /// Some services report multiple actors in `attributedTo`, e.g. PeerTube
+    /// lists both Person and channel Group, threadiverse software
+    /// could list community Group as well. Actors that are `audience`
+    /// are not authors, as well as Group actors, so the first Person-like
+    /// actor is preferred, then the first actor not in `audience`,
+    /// then just the first actor.
+    pub fn author_id(&self, audience: Option<&CompoundActorReference>) -> Option<&url::Url> {
         match self {
             Self::Reference(actor_ref) => Some(actor_ref.id()),
 
             Self::List(actor_refs) => {
-                // Some services like Peertube report multiple actors,
-                // e.g. Person and Group. As only single attribution entity
-                // stored per document in current index schema, there is need
-                // to choose which.
-
-                // First try to find non-group actor
-                let person_like_reference = actor_refs.iter()
-                    .find(|x|
-                        match x.entity_type() {
-                            None => false,
-
-                            Some(entity_type) => matches!(
-                                entity_type,
-                                EntityType::Person | EntityType::Service
-                            )
-                        }
-                    );
+                let audience_ids = audience
+                    .map(|audience| audience.as_id_vec())
+                    .unwrap_or_default();
+
+                let candidates: Vec<&ActorReference> = actor_refs.iter()
+                    .filter(|reference| !audience_ids.contains(&reference.id()))
+                    .collect();
+
+                let person_like_reference = candidates.iter()
+                    .find(|reference| matches!(
+                        reference.entity_type(),
+                        Some(EntityType::Person | EntityType::Service)
+                    ));
 
                 if let Some(actor_reference) = person_like_reference {
                     return Some(actor_reference.id());
                 }
 
-                // ok, any actor reference will do now
-                actor_refs.iter()
-                    .next()
-                    .map(|v| v.id())
+                let non_group_reference = candidates.iter()
+                    .find(|reference| reference.entity_type() != Some(EntityType::Group));
+
+                non_group_reference
+                    .or(candidates.first())
+                    .map(|reference| reference.id())
+                    .or_else(|| actor_refs.first().map(|reference| reference.id()))
             }
         }
     }
@@ -529,9 +604,11 @@ pub struct Endpoints {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PublicKey {
     /// ID of key
+    #[serde(deserialize_with = "deserialize_id_url")]
     pub id: url::Url,
 
     /// Who owns the key, actor itself is the usual owner.
+    #[serde(deserialize_with = "deserialize_id_url")]
     pub owner: url::Url,
 
     /// pem file condensed into single lines with end of lines escaped as `\n`.
@@ -563,27 +640,29 @@ impl PublicKeyReference {
     }
 
     /// This method return key matching `key_id` if reference contains it.
+    /// IDs are compared in canonical form, so portable key ID matches
+    /// key served by gateway.
     pub fn get_by_id(&self, key_id: &str) -> Option<&PublicKey> {
-        let key_url = match url::Url::parse(key_id) {
-            Ok(url) => url,
+        let key_id = match ObjectId::parse(key_id) {
+            Some(id) => id,
 
-            Err(err) => {
-                error!("Given key ID '{key_id}' is not valid URl: {err:?}");
+            None => {
+                error!("Given key ID '{key_id}' is not valid ID");
                 return None;
             }
         };
 
         match self {
-            PublicKeyReference::Single(public_key) => if public_key.id.eq(
-                &key_url
-            ) {
+            PublicKeyReference::Single(public_key) => if ObjectId::from(
+                &public_key.id
+            ) == key_id {
                 Some(public_key)
             } else {
                 None
             },
 
             PublicKeyReference::List(public_keys) => public_keys.iter()
-                .find(|key| key.id.eq(&key_url))
+                .find(|key| ObjectId::from(&key.id) == key_id)
         }
     }
 
@@ -599,6 +678,7 @@ impl PublicKeyReference {
 }
 
 /// Helper structure to represent actor as username and server it belongs to.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct ActorReadableId {
     /// Server as in Fediverse instance server.
     pub server: String,
@@ -606,6 +686,30 @@ pub struct ActorReadableId {
     pub username: String,
 }
 
+impl ActorReadableId {
+    /// Parses handle in `@username@server` or `username@server` form.
+    /// Server name is lowercased.
+    pub fn from_handle(handle: &str) -> Option<Self> {
+        let handle = handle.trim();
+        let handle = handle.strip_prefix('@').unwrap_or(handle);
+        let (username, server) = handle.split_once('@')?;
+
+        if username.is_empty() || server.is_empty() || server.contains('@') {
+            return None;
+        }
+
+        Some(Self {
+            server: server.to_lowercase(),
+            username: username.to_string(),
+        })
+    }
+
+    /// Returns handle in `@username@server` form.
+    pub fn handle(&self) -> String {
+        format!("@{}@{}", self.username, self.server)
+    }
+}
+
 /// This function returns discoverability state for `searchable_by` property.
 /// Content is discoverable if `searchable_by` contains either well-known
 /// Public reference or ot contains Fedineko specific not-really-used-by-anyone
@@ -631,7 +735,9 @@ pub fn is_public_searchable_by(
 
 #[cfg(test)]
 mod tests {
-    use crate::actor::{ActorReference, NONE_ADDRESSEE, PUBLIC_ADDRESSEE};
+    use crate::actor::{Actor, ActorReference, NONE_ADDRESSEE, PUBLIC_ADDRESSEE, PublicKeyReference};
+    use crate::id::ObjectId;
+    use crate::object::ObjectTrait;
 
     #[test]
     fn deserialize_nostr_like_public_actor_reference() {
@@ -649,4 +755,53 @@ mod tests {
         assert_eq!(reference.id().as_str(), NONE_ADDRESSEE);
         assert_eq!(reference.entity_type(), None);
     }
+
+    #[test]
+    fn portable_ids_match_gateway_urls() {
+        let did = "did:key:z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2";
+
+        let keys: PublicKeyReference = serde_json::from_value(serde_json::json!({
+            "id": format!("https://z.y.x/.well-known/apgateway/{did}/actor#main-key"),
+            "owner": format!("https://z.y.x/.well-known/apgateway/{did}/actor"),
+            "publicKeyPem": "-----BEGIN PUBLIC KEY-----\n-----END PUBLIC KEY-----"
+        })).unwrap();
+
+        assert!(keys.get_by_id(&format!("ap://{did}/actor#main-key")).is_some());
+        assert!(keys.get_by_id(&format!("ap://{did}/actor#other-key")).is_none());
+
+        let reference: ActorReference = serde_json::from_value(serde_json::json!(
+            format!("ap://{did}/actor")
+        )).unwrap();
+
+        assert!(reference.matches(&format!("https://a.b/.well-known/apgateway/{did}/actor")));
+    }
+
+    #[test]
+    fn portable_actor_is_deserialized() {
+        let did = "did:key:z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2";
+
+        let actor: Actor = serde_json::from_value(serde_json::json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "type": "Person",
+            "id": format!("ap://{did}/actor"),
+            "inbox": format!("ap://{did}/actor/inbox"),
+            "followers": format!("ap://{did}/actor/followers"),
+            "publicKey": {
+                "id": format!("ap://{did}/actor#main-key"),
+                "owner": format!("ap://{did}/actor"),
+                "publicKeyPem": "-----BEGIN PUBLIC KEY-----\n-----END PUBLIC KEY-----"
+            }
+        })).unwrap();
+
+        assert_eq!(
+            format!("ap://{did}/actor"),
+            ObjectId::from(actor.object_id()).canonical()
+        );
+
+        assert!(actor.matches(&format!("https://z.y.x/.well-known/apgateway/{did}/actor")));
+
+        let keys = actor.public_key.as_ref().unwrap();
+
+        assert!(keys.get_by_id(&format!("https://z.y.x/.well-known/apgateway/{did}/actor#main-key")).is_some());
+    }
 }
@@ -2,7 +2,53 @@ use std::fmt::{Debug, Formatter};
 
 use serde::{Deserialize, Serialize};
 
-use crate::entity::EntityType;
+use crate::content::deserialize_lenient;
+use crate::duration::DurationValue;
+use crate::entity::{EntityType, UnknownProperties};
+
+/// Kind of attachment derived from its type and media type.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum AttachmentKind {
+    /// Picture, e.g. photo or GIF.
+    Image,
+
+    /// Video, including animations sent as video.
+    Video,
+
+    /// Audio, e.g. music or voice message.
+    Audio,
+
+    /// Link to web page.
+    Link,
+
+    /// Profile metadata field, as Mastodon sends it for actors.
+    PropertyValue,
+
+    /// Document of other media type, e.g. PDF.
+    Document,
+
+    /// Attachment this crate could not classify.
+    Unknown,
+}
+
+/// Focal point of image, as Mastodon defines it.
+/// Both coordinates are in `[-1.0, 1.0]` range, `(0.0, 0.0)` is the center
+/// of image, `(-1.0, 1.0)` is its top left corner.
+/// See: <https://docs.joinmastodon.org/spec/activitypub/#focalPoint>
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct FocalPoint(pub f64, pub f64);
+
+impl FocalPoint {
+    /// Horizontal coordinate, from left `-1.0` to right `1.0`.
+    pub fn x(&self) -> f64 {
+        self.0
+    }
+
+    /// Vertical coordinate, from bottom `-1.0` to top `1.0`.
+    pub fn y(&self) -> f64 {
+        self.1
+    }
+}
 
 /// This structure represents ActivityPub Attachment.
 /// Attachment comes in many forms, e.g. it could be `PropertyValue`.
@@ -14,18 +60,59 @@ pub struct Attachment {
 
     /// Content associated with attachment.
     #[serde(alias = "value")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
 
     /// Name of attachment, e.g. property name.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// URL to attachment content.
     #[serde(alias = "href")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<url::Url>,
 
     /// Media type of attachment, e.g. image/jpeg.
-    #[serde(alias = "mediaType")]
+    /// Serialized as `mediaType`, `media_type` is still accepted.
+    #[serde(rename = "mediaType", alias = "media_type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
+
+    /// BlurHash of image or video preview.
+    /// See: <https://blurha.sh/>
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blurhash: Option<String>,
+
+    /// Point of interest of image to crop it around.
+    #[serde(rename = "focalPoint")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focal_point: Option<FocalPoint>,
+
+    /// Width of media in pixels.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// Height of media in pixels.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// Duration of audio or video.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<DurationValue>,
+
+    /// Flag to indicate that attachment is sensitive.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitive: Option<bool>,
+
+    /// Properties not modelled by this crate.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
 }
 
 /// Debug trait implementation to make attachment logged in a bit more readable form.
@@ -46,13 +133,81 @@ impl Debug for Attachment {
             .field("media_type", &self.media_type.as_deref()
                 .unwrap_or("")
             )
+            .field("width", &self.width)
+            .field("height", &self.height)
             .finish()
     }
 }
 
+impl Attachment {
+    /// Returns kind of attachment.
+    ///
+    /// Generic `Document` type, as Mastodon sends it for all media,
+    /// is classified by media type.
+    pub fn kind(&self) -> AttachmentKind {
+        match self.object_type {
+            EntityType::Image => AttachmentKind::Image,
+            EntityType::Video => AttachmentKind::Video,
+            EntityType::Audio => AttachmentKind::Audio,
+            EntityType::Link => AttachmentKind::Link,
+            EntityType::PropertyValue => AttachmentKind::PropertyValue,
+
+            EntityType::Document => self.kind_by_media_type()
+                .unwrap_or(AttachmentKind::Document),
+
+            _ => self.kind_by_media_type()
+                .unwrap_or(AttachmentKind::Unknown),
+        }
+    }
+
+    fn kind_by_media_type(&self) -> Option<AttachmentKind> {
+        let media_type = self.media_type.as_deref()?
+            .trim()
+            .to_ascii_lowercase();
+
+        let (top_level, _) = media_type.split_once('/')?;
+
+        match top_level {
+            "image" => Some(AttachmentKind::Image),
+            "video" => Some(AttachmentKind::Video),
+            "audio" => Some(AttachmentKind::Audio),
+            _ if media_type == "text/html" => Some(AttachmentKind::Link),
+            _ => Some(AttachmentKind::Document),
+        }
+    }
+
+    /// Returns `true` if attachment is image, video or audio.
+    pub fn is_media(&self) -> bool {
+        matches!(
+            self.kind(),
+            AttachmentKind::Image |
+            AttachmentKind::Video |
+            AttachmentKind::Audio
+        )
+    }
+
+    /// Returns alternative text of media attachment, if any.
+    /// `name` of other attachments, e.g. property name, is not alt text.
+    pub fn alt_text(&self) -> Option<&str> {
+        if !self.is_media() {
+            return None;
+        }
+
+        self.name.as_deref()
+            .filter(|name| !name.trim().is_empty())
+    }
+
+    /// Returns duration of audio or video, if valid.
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        self.duration.as_ref()
+            .and_then(|duration| duration.to_duration())
+    }
+}
+
 /// Helper to wrap single or multiple attachments.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum AttachmentReference {
     Single(Attachment),
     List(Vec<Attachment>),
@@ -75,3 +230,98 @@ impl AttachmentReference {
         }
     }
 }
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::attachment::{AttachmentKind, AttachmentReference, FocalPoint};
+
+    #[test]
+    fn test_attachment_kinds() {
+        let attachments: AttachmentReference = serde_json::from_str(r#"[
+          {
+            "type": "Document",
+            "mediaType": "image/png",
+            "url": "https://z.y.x/media/1.png",
+            "name": "A cat in a box",
+            "blurhash": "UBL_:rOpGG-oBUNG,qRj2so|=eE1w^n4S5NH",
+            "focalPoint": [-0.5, 0.25],
+            "width": 640,
+            "height": 480
+          },
+          {
+            "type": "Audio",
+            "url": "https://z.y.x/media/1.ogg",
+            "duration": 61.5
+          },
+          {
+            "type": "Document",
+            "mediaType": "video/mp4",
+            "url": "https://z.y.x/media/1.mp4",
+            "duration": "PT1M"
+          },
+          {
+            "type": "PropertyValue",
+            "name": "Pronouns",
+            "value": "they/them"
+          },
+          {
+            "type": "Document",
+            "mediaType": "application/pdf",
+            "url": "https://z.y.x/media/1.pdf"
+          }
+        ]"#).unwrap();
+
+        let attachments = attachments.as_vec();
+
+        let kinds: Vec<AttachmentKind> = attachments.iter()
+            .map(|attachment| attachment.kind())
+            .collect();
+
+        assert_eq!(
+            vec![
+                AttachmentKind::Image,
+                AttachmentKind::Audio,
+                AttachmentKind::Video,
+                AttachmentKind::PropertyValue,
+                AttachmentKind::Document,
+            ],
+            kinds
+        );
+
+        let image = attachments[0];
+
+        assert_eq!(Some("A cat in a box"), image.alt_text());
+        assert_eq!(Some(FocalPoint(-0.5, 0.25)), image.focal_point);
+        assert_eq!(Some(640), image.width);
+
+        assert_eq!(Some(Duration::from_millis(61_500)), attachments[1].duration());
+        assert_eq!(Some(Duration::from_secs(60)), attachments[2].duration());
+        assert_eq!(None, attachments[3].alt_text());
+    }
+
+    #[test]
+    fn test_malformed_media_properties_are_ignored() {
+        let attachments: AttachmentReference = serde_json::from_str(r#"{
+          "type": "Document",
+          "mediaType": "image/png",
+          "url": "https://z.y.x/media/1.png",
+          "width": 640.0,
+          "height": "480",
+          "focalPoint": [0.5],
+          "duration": [],
+          "blurhash": 1
+        }"#).unwrap();
+
+        let image = attachments.as_vec()[0];
+
+        assert_eq!(AttachmentKind::Image, image.kind());
+        assert_eq!(None, image.width);
+        assert_eq!(None, image.height);
+        assert_eq!(None, image.focal_point);
+        assert_eq!(None, image.duration());
+        assert_eq!(None, image.blurhash);
+    }
+}
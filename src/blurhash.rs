@@ -0,0 +1,442 @@
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+
+use crate::attachment::Attachment;
+
+/// Alphabet of base 83 encoding used by BlurHash.
+const BASE83_ALPHABET: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// The longest side of preview [decode_blurhash()] produces.
+/// BlurHash encodes a few components only, larger previews
+/// do not have more detail, just cost more.
+pub const MAX_PREVIEW_SIDE: u32 = 512;
+
+/// Errors returned when BlurHash could not be decoded.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlurhashError {
+    /// Hash is shorter than minimal 6 characters.
+    TooShort,
+
+    /// Hash length does not match number of components it declares.
+    LengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+
+    /// Hash has character outside of base 83 alphabet.
+    InvalidCharacter(char),
+
+    /// Requested preview has zero width or height, or either one
+    /// exceeds [MAX_PREVIEW_SIDE].
+    InvalidSize,
+}
+
+impl Display for BlurhashError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlurhashError::TooShort => write!(f, "blurhash is too short"),
+
+            BlurhashError::LengthMismatch { expected, actual } => write!(
+                f,
+                "blurhash length is {actual}, expected {expected}"
+            ),
+
+            BlurhashError::InvalidCharacter(c) => write!(
+                f,
+                "blurhash has invalid character '{c}'"
+            ),
+
+            BlurhashError::InvalidSize => write!(f, "preview size is invalid"),
+        }
+    }
+}
+
+impl std::error::Error for BlurhashError {}
+
+fn decode83(value: &str) -> Result<u32, BlurhashError> {
+    value.chars()
+        .try_fold(0u32, |result, c| {
+            let digit = BASE83_ALPHABET.iter()
+                .position(|a| *a as char == c)
+                .ok_or(BlurhashError::InvalidCharacter(c))?;
+
+            Ok(result * 83 + digit as u32)
+        })
+}
+
+/// Validates BlurHash and returns number of its horizontal
+/// and vertical components.
+/// See: <https://github.com/woltapp/blurhash/blob/master/Algorithm.md>
+pub fn validate_blurhash(hash: &str) -> Result<(usize, usize), BlurhashError> {
+    if let Some(c) = hash.chars().find(|c| !c.is_ascii() || !BASE83_ALPHABET.contains(&(*c as u8))) {
+        return Err(BlurhashError::InvalidCharacter(c));
+    }
+
+    if hash.len() < 6 {
+        return Err(BlurhashError::TooShort);
+    }
+
+    let size_flag = decode83(&hash[..1])? as usize;
+    let components_x = size_flag % 9 + 1;
+    let components_y = size_flag / 9 + 1;
+
+    let expected = 4 + 2 * components_x * components_y;
+
+    if hash.len() != expected {
+        return Err(BlurhashError::LengthMismatch {
+            expected,
+            actual: hash.len(),
+        });
+    }
+
+    Ok((components_x, components_y))
+}
+
+fn srgb_to_linear(value: u32) -> f64 {
+    let value = value as f64 / 255.0;
+
+    match value <= 0.04045 {
+        true => value / 12.92,
+        false => ((value + 0.055) / 1.055).powf(2.4),
+    }
+}
+
+fn linear_to_srgb(value: f64) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+
+    let srgb = match value <= 0.0031308 {
+        true => value * 12.92,
+        false => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+    };
+
+    (srgb * 255.0 + 0.5) as u8
+}
+
+fn sign_pow(value: f64, exponent: f64) -> f64 {
+    value.abs().powf(exponent).copysign(value)
+}
+
+/// Decoded BlurHash preview.
+#[derive(Debug, Clone)]
+pub struct BlurhashPreview {
+    /// Width of preview in pixels.
+    pub width: u32,
+
+    /// Height of preview in pixels.
+    pub height: u32,
+
+    /// Pixels row by row, 4 bytes per pixel in RGBA order.
+    pub pixels: Vec<u8>,
+}
+
+impl BlurhashPreview {
+    /// Encodes preview as PNG image.
+    pub fn to_png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.pixels)
+    }
+
+    /// Encodes preview as PNG `data:` URI, e.g. to be used in `<img src>`.
+    pub fn to_data_uri(&self) -> String {
+        format!("data:image/png;base64,{}", encode_base64(&self.to_png()))
+    }
+}
+
+/// Decodes BlurHash into `width` by `height` RGBA preview.
+///
+/// `punch` adjusts contrast, `1.0` keeps colours as encoded.
+/// Previews are meant to be small, e.g. 32 pixels wide,
+/// as browsers scale them up smoothly anyway, sides larger
+/// than [MAX_PREVIEW_SIDE] are rejected.
+pub fn decode_blurhash(
+    hash: &str,
+    width: u32,
+    height: u32,
+    punch: f64,
+) -> Result<BlurhashPreview, BlurhashError> {
+    let (components_x, components_y) = validate_blurhash(hash)?;
+
+    if width == 0 || height == 0 || width > MAX_PREVIEW_SIDE || height > MAX_PREVIEW_SIDE {
+        return Err(BlurhashError::InvalidSize);
+    }
+
+    let size = (width as usize).checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .ok_or(BlurhashError::InvalidSize)?;
+
+    let quantised_maximum = decode83(&hash[1..2])?;
+    let maximum = (quantised_maximum + 1) as f64 / 166.0 * punch;
+
+    let mut colors = Vec::with_capacity(components_x * components_y);
+
+    let dc = decode83(&hash[2..6])?;
+
+    colors.push([
+        srgb_to_linear(dc >> 16),
+        srgb_to_linear((dc >> 8) & 255),
+        srgb_to_linear(dc & 255),
+    ]);
+
+    for i in 1..components_x * components_y {
+        let ac = decode83(&hash[4 + i * 2..6 + i * 2])?;
+
+        let component = |quantised: u32| {
+            sign_pow((quantised as f64 - 9.0) / 9.0, 2.0) * maximum
+        };
+
+        colors.push([
+            component(ac / (19 * 19)),
+            component((ac / 19) % 19),
+            component(ac % 19),
+        ]);
+    }
+
+    let mut pixels = Vec::with_capacity(size);
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut pixel = [0.0f64; 3];
+
+            for j in 0..components_y {
+                for i in 0..components_x {
+                    let basis = (PI * x as f64 * i as f64 / width as f64).cos() *
+                        (PI * y as f64 * j as f64 / height as f64).cos();
+
+                    let color = colors[i + j * components_x];
+
+                    for (channel, value) in pixel.iter_mut().zip(color) {
+                        *channel += value * basis;
+                    }
+                }
+            }
+
+            pixels.extend(pixel.map(linear_to_srgb));
+            pixels.push(255);
+        }
+    }
+
+    Ok(BlurhashPreview {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Returns preview dimensions of given `aspect_ratio`, width to height,
+/// so that the longest side is `max_side` pixels.
+pub fn preview_dimensions(aspect_ratio: f64, max_side: u32) -> (u32, u32) {
+    let max_side = max_side.max(1);
+
+    if !aspect_ratio.is_finite() || aspect_ratio <= 0.0 {
+        return (max_side, max_side);
+    }
+
+    let scaled = |side: f64| (side.round() as u32).clamp(1, max_side);
+
+    match aspect_ratio >= 1.0 {
+        true => (max_side, scaled(max_side as f64 / aspect_ratio)),
+        false => (scaled(max_side as f64 * aspect_ratio), max_side),
+    }
+}
+
+impl Attachment {
+    /// Decodes `blurhash` of attachment into preview of the same aspect
+    /// ratio as attachment, the longest side is `max_side` pixels.
+    /// Square preview is returned if dimensions are unknown.
+    pub fn blurhash_preview(&self, max_side: u32) -> Option<Result<BlurhashPreview, BlurhashError>> {
+        let hash = self.blurhash.as_deref()?;
+
+        let aspect_ratio = match (self.width, self.height) {
+            (Some(width), Some(height)) if height > 0 => width as f64 / height as f64,
+            _ => 1.0,
+        };
+
+        let (width, height) = preview_dimensions(aspect_ratio, max_side);
+
+        Some(decode_blurhash(hash, width, height, 1.0))
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+
+    for byte in data {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB8_8320,
+                _ => crc >> 1,
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+fn push_png_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend(chunk_type);
+    png.extend(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Encodes RGBA pixels as PNG. Image data is not compressed,
+/// zlib stream consists of stored blocks only, that is fine
+/// for tiny previews.
+fn encode_png(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK_SIZE: usize = 65535;
+
+    let mut raw = Vec::with_capacity(pixels.len() + height as usize);
+
+    // fields of preview are public, so width could be anything
+    let row_length = (width as usize).saturating_mul(4).max(1);
+
+    for row in pixels.chunks(row_length) {
+        // filter type: none
+        raw.push(0);
+        raw.extend(row);
+    }
+
+    // zlib header: deflate, 32K window, no dictionary, fastest
+    let mut zlib = vec![0x78, 0x01];
+
+    let blocks = raw.chunks(MAX_BLOCK_SIZE).count().max(1);
+
+    for (index, block) in raw.chunks(MAX_BLOCK_SIZE).enumerate() {
+        let is_final = index + 1 == blocks;
+        let length = block.len() as u16;
+
+        zlib.push(is_final as u8);
+        zlib.extend(length.to_le_bytes());
+        zlib.extend((!length).to_le_bytes());
+        zlib.extend(block);
+    }
+
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+
+    // bit depth 8, color type RGBA, default compression, filter, no interlace
+    header.extend([8, 6, 0, 0, 0]);
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    push_png_chunk(&mut png, b"IHDR", &header);
+    push_png_chunk(&mut png, b"IDAT", &zlib);
+    push_png_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+/// Encodes `data` as standard base64 with padding.
+fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => {
+                    let index = (triple >> (18 - 6 * i)) & 0x3F;
+                    encoded.push(ALPHABET[index as usize] as char);
+                }
+
+                false => encoded.push('='),
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blurhash::{
+        BlurhashError,
+        crc32,
+        decode_blurhash,
+        encode_base64,
+        MAX_PREVIEW_SIDE,
+        preview_dimensions,
+        validate_blurhash,
+    };
+
+    #[test]
+    fn test_blurhash_validation() {
+        assert_eq!(Ok((4, 3)), validate_blurhash("LEHV6nWB2yk8pyo0adR*.7kCMdnj"));
+        assert_eq!(Err(BlurhashError::TooShort), validate_blurhash("LEHV"));
+        assert_eq!(Err(BlurhashError::InvalidCharacter('!')), validate_blurhash("LEHV6nWB2yk8pyo0adR*.7kCMdn!"));
+
+        assert_eq!(
+            Err(BlurhashError::LengthMismatch { expected: 28, actual: 27 }),
+            validate_blurhash("LEHV6nWB2yk8pyo0adR*.7kCMdn")
+        );
+    }
+
+    #[test]
+    fn test_blurhash_decoding() {
+        let preview = decode_blurhash("LEHV6nWB2yk8pyo0adR*.7kCMdnj", 32, 24, 1.0).unwrap();
+
+        assert_eq!(32 * 24 * 4, preview.pixels.len());
+        assert!(preview.pixels.chunks(4).all(|pixel| pixel[3] == 255));
+
+        // single DC component is a solid colour
+        let preview = decode_blurhash("00000s", 2, 2, 1.0).unwrap();
+        assert!(preview.pixels.chunks(4).all(|pixel| pixel == [0, 0, 54, 255]));
+
+        let png = preview.to_png();
+
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!([0xAE, 0x42, 0x60, 0x82], png[png.len() - 4..]);
+        assert!(preview.to_data_uri().starts_with("data:image/png;base64,iVBORw0KGgo"));
+
+        assert_eq!(
+            Err(BlurhashError::InvalidSize),
+            decode_blurhash("000000", 70000, 70000, 1.0).map(|preview| preview.width)
+        );
+
+        assert_eq!(
+            Err(BlurhashError::InvalidSize),
+            decode_blurhash("000000", MAX_PREVIEW_SIDE + 1, 1, 1.0).map(|preview| preview.width)
+        );
+
+        assert_eq!((32, 18), preview_dimensions(16.0 / 9.0, 32));
+        assert_eq!((24, 32), preview_dimensions(0.75, 32));
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!("TWFu", encode_base64(b"Man"));
+        assert_eq!("TWE=", encode_base64(b"Ma"));
+        assert_eq!("TQ==", encode_base64(b"M"));
+    }
+}
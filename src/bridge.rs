@@ -0,0 +1,292 @@
+use std::fmt;
+
+use crate::actor::Actor;
+use crate::content::Content;
+use crate::object::{Object, ObjectTrait};
+use crate::object_guesser::extract_username_from_url;
+
+/// Host of Bridgy Fed's ATProto (Bluesky) bridge.
+const BRIDGY_FED_ATPROTO_HOST: &str = "bsky.brid.gy";
+
+/// Host of Bluesky web application.
+const BLUESKY_HOST: &str = "bsky.app";
+
+/// Hosts of Bridgy Fed's web bridge, `fed.brid.gy` is the older one.
+const BRIDGY_FED_WEB_HOSTS: [&str; 2] = ["web.brid.gy", "fed.brid.gy"];
+
+/// Host of Mostr, Nostr bridge.
+const MOSTR_HOST: &str = "mostr.pub";
+
+/// Hosts of Threads.
+const THREADS_HOSTS: [&str; 2] = ["threads.net", "www.threads.net"];
+
+/// Network bridged content originates from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OriginNetwork {
+    /// Bluesky and other ATProto services.
+    AtProto,
+
+    /// Websites, e.g. blogs with microformats or feeds.
+    Web,
+
+    /// Nostr relays.
+    Nostr,
+
+    /// Threads by Meta.
+    Threads,
+}
+
+impl OriginNetwork {
+    /// Returns `true` if accounts of this network are bridged only
+    /// once their owners opt in, as Bridgy Fed and Threads do.
+    /// Mostr bridges any Nostr account. Opting in to bridging is not
+    /// necessarily consent to be indexed.
+    pub fn is_opt_in(&self) -> bool {
+        match self {
+            OriginNetwork::AtProto |
+            OriginNetwork::Web |
+            OriginNetwork::Threads => true,
+            OriginNetwork::Nostr => false,
+        }
+    }
+}
+
+impl fmt::Display for OriginNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Bridge or proxy actor or content comes through, with identifier
+/// of original account or object in origin network, if known.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Bridge {
+    /// Bridgy Fed's ATProto bridge, identifier is DID, e.g. `did:plc:xyz`,
+    /// or handle.
+    BridgyFedAtProto(Option<String>),
+
+    /// Bridgy Fed's web bridge, identifier is website URL.
+    BridgyFedWeb(Option<String>),
+
+    /// Mostr, identifier is `npub`, `note` or similar NIP-19 entity,
+    /// or hex public key.
+    Mostr(Option<String>),
+
+    /// Threads, identifier is username or numeric user ID.
+    Threads(Option<String>),
+}
+
+impl Bridge {
+    /// Returns network bridged content originates from.
+    pub fn origin_network(&self) -> OriginNetwork {
+        match self {
+            Bridge::BridgyFedAtProto(_) => OriginNetwork::AtProto,
+            Bridge::BridgyFedWeb(_) => OriginNetwork::Web,
+            Bridge::Mostr(_) => OriginNetwork::Nostr,
+            Bridge::Threads(_) => OriginNetwork::Threads,
+        }
+    }
+
+    /// Returns identifier in origin network, if known.
+    pub fn origin_id(&self) -> Option<&str> {
+        match self {
+            Bridge::BridgyFedAtProto(id) |
+            Bridge::BridgyFedWeb(id) |
+            Bridge::Mostr(id) |
+            Bridge::Threads(id) => id.as_deref(),
+        }
+    }
+
+    /// Detects bridge from `id` of actor or content and its `url` links.
+    ///
+    /// Bridge is recognised by ID host only, as anyone could mention
+    /// bridge in profile. Links to origin network, e.g. Bluesky profile
+    /// or `nostr:` canonical links, provide identifier.
+    fn detect(id: &url::Url, urls: &[&url::Url]) -> Option<Self> {
+        let host = id.host_str()?;
+
+        if host == BRIDGY_FED_ATPROTO_HOST {
+            let from_path = id.path_segments()
+                .and_then(|mut segments| segments.find(|segment| segment.starts_with("did:")))
+                .map(|did| did.to_string());
+
+            let from_urls = urls.iter()
+                .filter(|url| url.host_str() == Some(BLUESKY_HOST))
+                .find_map(|url| url.path().strip_prefix("/profile/"))
+                .and_then(|path| path.split('/').next())
+                .map(|id| id.to_string());
+
+            return Some(Bridge::BridgyFedAtProto(from_path.or(from_urls)));
+        }
+
+        if BRIDGY_FED_WEB_HOSTS.contains(&host) {
+            let path = id.path().trim_start_matches('/');
+
+            let origin_id = match path.strip_prefix("r/") {
+                Some(url) => Some(url.to_string()),
+                None => path.split('/')
+                    .next()
+                    .filter(|domain| domain.contains('.'))
+                    .map(|domain| format!("https://{domain}/")),
+            };
+
+            return Some(Bridge::BridgyFedWeb(origin_id));
+        }
+
+        if host == MOSTR_HOST {
+            let from_urls = urls.iter()
+                .find(|url| url.scheme() == "nostr")
+                .map(|url| url.path().to_string());
+
+            let from_path = id.path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.to_string());
+
+            return Some(Bridge::Mostr(from_urls.or(from_path)));
+        }
+
+        if THREADS_HOSTS.contains(&host) {
+            let username = urls.iter()
+                .filter(|url| url.host_str().map(|host| THREADS_HOSTS.contains(&host)).unwrap_or(false))
+                .find_map(|url| url.path()
+                    .strip_prefix("/@")
+                    .map(|username| username.trim_end_matches('/').to_string())
+                )
+                .or_else(|| extract_username_from_url(id));
+
+            return Some(Bridge::Threads(username));
+        }
+
+        None
+    }
+
+    fn detect_for_object(object: &Object) -> Option<Self> {
+        let urls = object.url.as_ref()
+            .map(|reference| reference.as_vec())
+            .unwrap_or_default();
+
+        Self::detect(object.object_id(), &urls)
+    }
+}
+
+impl Actor {
+    /// Returns bridge this actor is proxied by, if any.
+    pub fn bridge(&self) -> Option<Bridge> {
+        Bridge::detect_for_object(&self.object_entity)
+    }
+
+    /// Returns network this actor originates from, if it is bridged.
+    /// Bridges have their own consent semantics, so it is up to caller
+    /// to decide what it means for discoverability.
+    pub fn origin_network(&self) -> Option<OriginNetwork> {
+        self.bridge()
+            .map(|bridge| bridge.origin_network())
+    }
+}
+
+impl Content {
+    /// Returns bridge this content is proxied by, if any.
+    pub fn bridge(&self) -> Option<Bridge> {
+        Bridge::detect_for_object(&self.object_entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::Actor;
+    use crate::bridge::{Bridge, OriginNetwork};
+    use crate::content::Content;
+    use crate::discoverable::{AllowReason, Discoverable};
+
+    fn actor(id: &str, url: serde_json::Value) -> Actor {
+        serde_json::from_value(serde_json::json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "type": "Person",
+            "id": id,
+            "inbox": format!("{id}/inbox"),
+            "url": url
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_bridged_actors() {
+        let bluesky = actor(
+            "https://bsky.brid.gy/ap/did:plc:abcdef",
+            serde_json::json!("https://bsky.app/profile/cat.bsky.social"),
+        );
+
+        assert_eq!(Some(Bridge::BridgyFedAtProto(Some("did:plc:abcdef".to_string()))), bluesky.bridge());
+
+        let custom_domain = actor(
+            "https://cat.z.y.x/ap/did:plc:abcdef",
+            serde_json::json!("https://bsky.app/profile/cat.z.y.x"),
+        );
+
+        assert_eq!(None, custom_domain.bridge());
+
+        let web = actor("https://web.brid.gy/cats.z.y.x", serde_json::json!("https://cats.z.y.x/"));
+
+        assert_eq!(Some(Bridge::BridgyFedWeb(Some("https://cats.z.y.x/".to_string()))), web.bridge());
+
+        let nostr = actor(
+            "https://mostr.pub/users/0123abcd",
+            serde_json::json!([
+                {"type": "Link", "rel": "canonical", "href": "nostr:npub1xyz"},
+                {"type": "Link", "rel": "alternate", "mediaType": "text/html", "href": "https://njump.me/npub1xyz"}
+            ]),
+        );
+
+        assert_eq!(Some(Bridge::Mostr(Some("npub1xyz".to_string()))), nostr.bridge());
+        assert_eq!(Some(OriginNetwork::Nostr), nostr.origin_network());
+
+        let threads = actor(
+            "https://threads.net/ap/users/17841400000000000",
+            serde_json::json!("https://www.threads.net/@cat"),
+        );
+
+        assert_eq!(Some(Bridge::Threads(Some("cat".to_string()))), threads.bridge());
+
+        let native = actor("https://z.y.x/users/xyz", serde_json::json!("https://z.y.x/@xyz"));
+
+        assert_eq!(None, native.bridge());
+    }
+
+    #[test]
+    fn test_profile_text_does_not_make_actor_bridged() {
+        let actor: Actor = serde_json::from_value(serde_json::json!({
+            "@context": "https://www.w3.org/ns/activitystreams",
+            "type": "Person",
+            "id": "https://z.y.x/users/xyz",
+            "inbox": "https://z.y.x/users/xyz/inbox",
+            "attachment": [
+                {
+                    "type": "PropertyValue",
+                    "name": "Nostr",
+                    "value": "follow me via <a href=\"https://mostr.pub/\">mostr.pub</a>"
+                }
+            ]
+        })).unwrap();
+
+        assert_eq!(None, actor.bridge());
+
+        assert!(matches!(
+            actor.get_discoverable_state(),
+            Discoverable::Allowed(AllowReason::Assumed)
+        ));
+    }
+
+    #[test]
+    fn test_bridged_content() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://bsky.brid.gy/convert/ap/at://did:plc:abcdef/app.bsky.feed.post/3k",
+          "type": "Note",
+          "attributedTo": "https://bsky.brid.gy/ap/did:plc:abcdef",
+          "published": "2024-01-01T00:00:00Z",
+          "url": "https://bsky.app/profile/did:plc:abcdef/post/3k",
+          "content": "<p>Meow</p>"
+        }"#).unwrap();
+
+        assert_eq!(OriginNetwork::AtProto, content.bridge().unwrap().origin_network());
+    }
+}
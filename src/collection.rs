@@ -0,0 +1,410 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::entity::{Entity, EntityType};
+
+/// Default limit of pages [CollectionPages] fetches.
+pub const DEFAULT_MAX_PAGES: usize = 100;
+
+/// Item of collection, either embedded object or reference to it.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum CollectionItem {
+    /// Item is referenced by URL, e.g. follower.
+    Url(url::Url),
+
+    /// Embedded object, e.g. activity of outbox.
+    Object(serde_json::Value),
+}
+
+impl CollectionItem {
+    /// Returns ID of item, if it has one.
+    pub fn id(&self) -> Option<url::Url> {
+        match self {
+            CollectionItem::Url(url) => Some(url.clone()),
+
+            CollectionItem::Object(value) => value.get("id")
+                .and_then(|id| id.as_str())
+                .and_then(|id| url::Url::parse(id).ok()),
+        }
+    }
+
+    /// Returns embedded item as `T`, e.g. [crate::activity::Activity].
+    /// `None` is returned for references and items not matching `T`.
+    pub fn as_object<T: DeserializeOwned>(&self) -> Option<T> {
+        match self {
+            CollectionItem::Url(_) => None,
+            CollectionItem::Object(value) => serde_json::from_value(value.clone()).ok(),
+        }
+    }
+}
+
+/// Helper enumeration to deal with pages referenced in `first`, `last`,
+/// `next` and `prev` properties. Mastodon embeds first page of replies,
+/// others link pages by URL.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum CollectionReference {
+    /// Page is referenced by URL.
+    Url(url::Url),
+
+    /// Embedded page.
+    Collection(Box<Collection>),
+}
+
+impl CollectionReference {
+    /// Returns ID of referenced page, embedded pages could lack one.
+    pub fn id(&self) -> Option<&url::Url> {
+        match self {
+            CollectionReference::Url(url) => Some(url),
+            CollectionReference::Collection(collection) => collection.id.as_ref(),
+        }
+    }
+}
+
+/// Collection, ordered collection or page of either one.
+/// See: <https://www.w3.org/TR/activitystreams-core/#collections>
+///
+/// Example:
+/// ```json
+/// {
+///   "type": "OrderedCollection",
+///   "id": "https://z.y.x/users/xyz/outbox",
+///   "totalItems": 42,
+///   "first": "https://z.y.x/users/xyz/outbox?page=true",
+///   "last": "https://z.y.x/users/xyz/outbox?min_id=0&page=true"
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Collection {
+    /// Embedded [Entity] properties.
+    #[serde(flatten)]
+    pub entity: Entity,
+
+    /// Collection ID, embedded pages could lack one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<url::Url>,
+
+    /// Number of items in the whole collection.
+    #[serde(rename = "totalItems")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_items: Option<u64>,
+
+    /// First page of collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first: Option<CollectionReference>,
+
+    /// Last page of collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last: Option<CollectionReference>,
+
+    /// Next page, set for pages only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<CollectionReference>,
+
+    /// Previous page, set for pages only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<CollectionReference>,
+
+    /// Collection page belongs to, set for pages only.
+    #[serde(rename = "partOf")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part_of: Option<url::Url>,
+
+    /// Items of unordered collection or page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<CollectionItem>>,
+
+    /// Items of ordered collection or page.
+    #[serde(rename = "orderedItems")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ordered_items: Option<Vec<CollectionItem>>,
+}
+
+impl Collection {
+    /// Returns type of collection, e.g. `OrderedCollection`
+    /// or `CollectionPage`.
+    pub fn entity_type(&self) -> EntityType {
+        self.entity.object_type
+    }
+
+    /// Returns reference to page following this one: `next` of page,
+    /// or `first` page of collection.
+    ///
+    /// [Collection::pages()] follows these references, callers fetching
+    /// pages asynchronously could do the same in their own loop.
+    pub fn next_page(&self) -> Option<&CollectionReference> {
+        match self.is_page() {
+            true => self.next.as_ref(),
+            false => self.first.as_ref(),
+        }
+    }
+
+    /// Returns `true` if this is a page of collection.
+    pub fn is_page(&self) -> bool {
+        matches!(
+            self.entity_type(),
+            EntityType::CollectionPage | EntityType::OrderedCollectionPage
+        ) || self.part_of.is_some()
+    }
+
+    /// Returns items embedded into this collection or page,
+    /// regardless of whether collection is ordered.
+    pub fn items(&self) -> Vec<&CollectionItem> {
+        self.items.iter()
+            .chain(self.ordered_items.iter())
+            .flatten()
+            .collect()
+    }
+
+    /// Returns iterator over pages of this collection, pages are
+    /// fetched with `fetcher`, at most `max_pages` of them.
+    ///
+    /// This collection itself is the first page if it has items,
+    /// e.g. small collections are not paginated.
+    pub fn pages<F: CollectionFetcher>(self, fetcher: F, max_pages: usize) -> CollectionPages<F> {
+        let mut visited = HashSet::new();
+
+        if let Some(id) = &self.id {
+            visited.insert(id.clone());
+        }
+
+        let next = match self.items().is_empty() {
+            true => self.first.or(self.next),
+            false => Some(CollectionReference::Collection(Box::new(self))),
+        };
+
+        CollectionPages {
+            fetcher,
+            next,
+            visited,
+            pages: 0,
+            max_pages,
+            done: false,
+        }
+    }
+}
+
+/// Fetches collection pages for [CollectionPages], e.g. with HTTP client
+/// signing requests.
+///
+/// Fetching is blocking, as this crate does not depend on any async
+/// runtime. Async callers are better off following
+/// [Collection::next_page()] themselves.
+pub trait CollectionFetcher {
+    type Error;
+
+    /// Fetches collection or page by `url`.
+    fn fetch(&mut self, url: &url::Url) -> Result<Collection, Self::Error>;
+}
+
+/// Reasons for [CollectionPages] to stop.
+#[derive(Debug)]
+pub enum CollectionError<E> {
+    /// Fetcher failed.
+    Fetch(E),
+
+    /// Page was seen before, e.g. `next` refers previous page.
+    Loop(url::Url),
+
+    /// Limit of pages is reached, collection could have more.
+    PageLimit(usize),
+}
+
+impl<E: fmt::Display> fmt::Display for CollectionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CollectionError::Fetch(err) => write!(
+                f, "failed to fetch page: {err}"
+            ),
+
+            CollectionError::Loop(url) => write!(
+                f, "page {url} is already seen"
+            ),
+
+            CollectionError::PageLimit(limit) => write!(
+                f, "limit of {limit} pages is reached"
+            ),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for CollectionError<E> {}
+
+/// Iterator over pages of collection following `first` and `next` links,
+/// see [Collection::pages()].
+///
+/// Iteration stops after the last page or the first error.
+pub struct CollectionPages<F: CollectionFetcher> {
+    fetcher: F,
+    next: Option<CollectionReference>,
+    visited: HashSet<url::Url>,
+    pages: usize,
+    max_pages: usize,
+    done: bool,
+}
+
+impl<F: CollectionFetcher> CollectionPages<F> {
+    fn fetch(&mut self, reference: CollectionReference) -> Result<Collection, CollectionError<F::Error>> {
+        if self.pages >= self.max_pages {
+            return Err(CollectionError::PageLimit(self.max_pages));
+        }
+
+        let page = match reference {
+            CollectionReference::Collection(page) => *page,
+
+            CollectionReference::Url(url) => {
+                if !self.visited.insert(url.clone()) {
+                    return Err(CollectionError::Loop(url));
+                }
+
+                self.fetcher.fetch(&url)
+                    .map_err(CollectionError::Fetch)?
+            }
+        };
+
+        if let Some(id) = &page.id {
+            self.visited.insert(id.clone());
+        }
+
+        self.pages += 1;
+
+        Ok(page)
+    }
+}
+
+impl<F: CollectionFetcher> Iterator for CollectionPages<F> {
+    type Item = Result<Collection, CollectionError<F::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let reference = match self.next.take() {
+            None => {
+                self.done = true;
+                return None;
+            }
+
+            Some(reference) => reference,
+        };
+
+        match self.fetch(reference) {
+            Ok(page) => {
+                // Collection fetched instead of page is followed
+                // to its first page.
+                self.next = page.next_page().cloned();
+
+                Some(Ok(page))
+            }
+
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::collection::{Collection, CollectionError, CollectionFetcher};
+
+    struct Pages(HashMap<String, serde_json::Value>);
+
+    impl CollectionFetcher for Pages {
+        type Error = String;
+
+        fn fetch(&mut self, url: &url::Url) -> Result<Collection, Self::Error> {
+            let page = self.0.get(url.as_str())
+                .ok_or(format!("{url} is not found"))?;
+
+            serde_json::from_value(page.clone())
+                .map_err(|err| err.to_string())
+        }
+    }
+
+    fn outbox() -> Collection {
+        serde_json::from_str(r#"{
+          "@context": "https://www.w3.org/ns/activitystreams",
+          "type": "OrderedCollection",
+          "id": "https://z.y.x/users/xyz/outbox",
+          "totalItems": 3,
+          "first": "https://z.y.x/users/xyz/outbox?page=1"
+        }"#).unwrap()
+    }
+
+    fn page(number: u32, next: u32) -> (String, serde_json::Value) {
+        let id = format!("https://z.y.x/users/xyz/outbox?page={number}");
+
+        let page = serde_json::json!({
+            "type": "OrderedCollectionPage",
+            "id": id,
+            "partOf": "https://z.y.x/users/xyz/outbox",
+            "next": format!("https://z.y.x/users/xyz/outbox?page={next}"),
+            "orderedItems": [
+                {
+                    "type": "Create",
+                    "id": format!("https://z.y.x/users/xyz/statuses/{number}/activity"),
+                    "actor": "https://z.y.x/users/xyz",
+                    "object": format!("https://z.y.x/users/xyz/statuses/{number}")
+                }
+            ]
+        });
+
+        (id, page)
+    }
+
+    #[test]
+    fn test_pages_are_iterated() {
+        let fetcher = Pages(HashMap::from([page(1, 2), page(2, 3)]));
+        let pages: Vec<_> = outbox().pages(fetcher, 10).collect();
+
+        assert_eq!(3, pages.len());
+        assert_eq!(1, pages[0].as_ref().unwrap().items().len());
+
+        assert_eq!(
+            "https://z.y.x/users/xyz/statuses/2/activity",
+            pages[1].as_ref().unwrap().items()[0].id().unwrap().as_str()
+        );
+
+        assert!(matches!(pages[2], Err(CollectionError::Fetch(_))));
+    }
+
+    #[test]
+    fn test_next_page() {
+        assert_eq!(
+            "https://z.y.x/users/xyz/outbox?page=1",
+            outbox().next_page().unwrap().id().unwrap().as_str()
+        );
+
+        let (_, page) = page(1, 2);
+        let page: Collection = serde_json::from_value(page).unwrap();
+
+        assert_eq!(
+            "https://z.y.x/users/xyz/outbox?page=2",
+            page.next_page().unwrap().id().unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn test_loops_and_limits_stop_iteration() {
+        let fetcher = Pages(HashMap::from([page(1, 2), page(2, 1)]));
+        let pages: Vec<_> = outbox().pages(fetcher, 10).collect();
+
+        assert_eq!(3, pages.len());
+        assert!(matches!(pages[2], Err(CollectionError::Loop(_))));
+
+        let fetcher = Pages(HashMap::from([page(1, 2), page(2, 1)]));
+        let pages: Vec<_> = outbox().pages(fetcher, 1).collect();
+
+        assert_eq!(2, pages.len());
+        assert!(matches!(pages[1], Err(CollectionError::PageLimit(1))));
+    }
+}
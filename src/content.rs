@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 use log::warn;
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde::de::DeserializeOwned;
 
 use crate::actor::{
     CompoundActorReference,
@@ -11,16 +12,34 @@ use crate::actor::{
 };
 
 use crate::attachment::AttachmentReference;
+use crate::collection::{CollectionItem, CollectionReference};
 use crate::context::Context;
 use crate::discoverable::{AllowReason, DenyReason, Discoverable};
+use crate::duration::DurationValue;
 use crate::entity::EntityType;
+use crate::event::{EventStatus, JoinMode, Place};
 use crate::image::ImageReference;
-use crate::object::{Object, ObjectTrait};
+use crate::interaction_policy::InteractionPolicy;
+use crate::language::{
+    best_localized,
+    LanguageOrigin,
+    LocalizedContent,
+    normalize_language_map,
+    UNDETERMINED_LANGUAGE,
+};
+use crate::object::{Object, ObjectReference, ObjectTrait};
+use crate::poll::{PollClosed, PollOption};
+use crate::source::SourceReference;
 use crate::tag::TagReference;
+use crate::video::{Subtitle, VideoLabel, VideoPreview};
 
 /// This structure represents content, such as Note.
 /// In other words: toots, blog posts and so on.
-#[derive(Deserialize, Debug, Clone)]
+///
+/// With `unknown_properties` feature, properties this crate does not model
+/// are kept in [Entity::unknown_properties](crate::entity::Entity::unknown_properties)
+/// of embedded object, so content serialized back does not lose them.
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Content {
     /// Properties inherited from ActivityPub Object.
     #[serde(flatten)]
@@ -34,20 +53,60 @@ pub struct Content {
     /// but will be here just in case someone actually uses it.
     /// it is property to specify search indexing consent.
     /// See: <https://codeberg.org/fediverse/fep/src/branch/main/fep/5feb/fep-5feb.md>
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub indexable: Option<bool>,
 
     /// Is not really expected in Content object,
     /// but will be here just in case someone actually uses it.
     /// Older way to specify discoverability flag that was for accounts/Actors,
     /// but somehow became a common thing for content as well.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub discoverable: Option<bool>,
 
     /// Point of time this content was published.
     pub published: chrono::DateTime<chrono::Utc>,
 
     /// Flag to indicate that content is sensitive.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sensitive: Option<bool>,
 
+    /// Community content is posted to, threadiverse software,
+    /// e.g. Lemmy, sets it to Group actor.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audience: Option<CompoundActorReference>,
+
+    /// Flag to indicate whether replies are accepted, `false` for
+    /// locked threadiverse posts.
+    #[serde(rename = "commentsEnabled")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments_enabled: Option<bool>,
+
+    /// Flag to indicate that post is pinned in community.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stickied: Option<bool>,
+
+    /// GoToSocial's policy of who could like, reply to or announce content.
+    #[serde(rename = "interactionPolicy")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interaction_policy: Option<InteractionPolicy>,
+
+    /// Approval of this reply by author of replied content,
+    /// older GoToSocial's way, superseded by `replyAuthorization`.
+    #[serde(rename = "approvedBy")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<url::Url>,
+
+    /// Reference to `ReplyAuthorization` of this reply.
+    #[serde(rename = "replyAuthorization")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_authorization: Option<url::Url>,
+
     /// Fedibird's extension applicable to posts as well as to actors,
     /// indicates actor's consent for indexing servers to index ("searchable")
     /// this content.
@@ -71,27 +130,230 @@ pub struct Content {
     /// ]
     /// ```
     #[serde(rename = "searchableBy")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub searchable_by: Option<Vec<url::Url>>,
 
     /// Content summary, e.g. title or snippet of content.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
 
+    /// Summary in multiple languages.
+    #[serde(rename = "summaryMap")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary_map: Option<ContentMap>,
+
+    /// Name in multiple languages, usually set for articles and pages.
+    #[serde(rename = "nameMap")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_map: Option<ContentMap>,
+
     /// Full content data.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
 
+    /// Source markup `content` was rendered from, if shared.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceReference>,
+
+    /// Misskey's extension, content in Misskey Flavored Markdown.
+    /// It predates `source` property and duplicates it in recent versions.
+    #[serde(rename = "_misskey_content")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub misskey_content: Option<String>,
+
     /// If localized into multiple languages, those will be
     /// part of `content_map`.
     /// Often - but not always - could be used to guess language of content.
     #[serde(rename = "contentMap")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub content_map: Option<ContentMap>,
 
     /// Associated with content tags, including `#hastags`, mentions and emojis.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tag: Option<TagReference>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub attachment: Option<AttachmentReference>,
 
     /// Image associated with content.
     #[serde(alias = "image")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<ImageReference>,
+
+    /// Object this content replies to.
+    #[serde(rename = "inReplyTo")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<ObjectReference>,
+
+    /// Conversation this content belongs to.
+    /// Not to be confused with `@context` that defines schema.
+    /// See: <https://codeberg.org/fediverse/fep/src/branch/main/fep/7888/fep-7888.md>
+    #[serde(rename = "context")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_context: Option<ObjectReference>,
+
+    /// Collection of replies, Mastodon embeds its first page.
+    /// Malformed collections are ignored, as are ones below.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replies: Option<CollectionReference>,
+
+    /// Collection of likes, usually with `totalItems` only.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub likes: Option<CollectionReference>,
+
+    /// Collection of announces, usually with `totalItems` only.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shares: Option<CollectionReference>,
+
+    /// OStatus conversation identifier, Mastodon sets it
+    /// to `tag:` URI, e.g. `tag:x.y,2024-01-01:objectId=1:objectType=Conversation`.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation: Option<String>,
+
+    /// Quoted object as set by Fedibird, Akkoma and others.
+    #[serde(rename = "quoteUrl")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_url: Option<url::Url>,
+
+    /// Quoted object as set by Misskey and its forks.
+    #[serde(rename = "_misskey_quote")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub misskey_quote: Option<url::Url>,
+
+    /// Quoted object as set by some Misskey forks and Fedibird.
+    #[serde(rename = "quoteUri")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_uri: Option<url::Url>,
+
+    /// Options of single choice poll, set for `Question`.
+    /// Malformed poll properties are ignored, as are ones below.
+    #[serde(rename = "oneOf")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<PollOption>>,
+
+    /// Options of multiple choice poll, set for `Question`.
+    #[serde(rename = "anyOf")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<PollOption>>,
+
+    /// Point of time poll ends.
+    #[serde(rename = "endTime")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Indicates poll is closed.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed: Option<PollClosed>,
+
+    /// Mastodon's extension, number of people voted in poll.
+    #[serde(rename = "votersCount")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voters_count: Option<u64>,
+
+    /// Duration of video or audio, e.g. `PT92S`.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<DurationValue>,
+
+    /// PeerTube's extension, number of video views.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub views: Option<u64>,
+
+    /// PeerTube's extension, category of video.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<VideoLabel>,
+
+    /// PeerTube's extension, licence of video.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub licence: Option<VideoLabel>,
+
+    /// PeerTube's extension, language of video.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<VideoLabel>,
+
+    /// PeerTube's extension, subtitles available for video.
+    #[serde(rename = "subtitleLanguage")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle_language: Option<Vec<Subtitle>>,
+
+    /// Previews of video, e.g. storyboards.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<Vec<VideoPreview>>,
+
+    /// Point of time event starts.
+    #[serde(rename = "startTime")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// IANA time zone of event, e.g. `Europe/Paris`.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+
+    /// Place event takes place at.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Place>,
+
+    /// Mobilizon's extension, how people could join event.
+    #[serde(rename = "joinMode")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub join_mode: Option<JoinMode>,
+
+    /// Status of event.
+    #[serde(rename = "ical:status")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_status: Option<EventStatus>,
+
+    /// Mobilizon's extension, number of event participants.
+    #[serde(rename = "participantCount")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub participant_count: Option<u64>,
+
+    /// Maximum number of event participants, `0` means unlimited.
+    #[serde(rename = "maximumAttendeeCapacity")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum_attendee_capacity: Option<u64>,
+
+    /// Number of places left at event.
+    #[serde(rename = "remainingAttendeeCapacity")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_attendee_capacity: Option<u64>,
+
+    /// Mobilizon's extension, `true` if event takes place online.
+    #[serde(rename = "isOnline")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_online: Option<bool>,
 }
 
 impl ObjectTrait for Content {
@@ -109,7 +371,7 @@ impl ObjectTrait for Content {
 }
 
 /// Helper struct to deal with different types of `contentMap` definition.
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum ContentMap {
     /// A proper dictionary of languages mapped to content.
@@ -136,40 +398,279 @@ impl ContentMap {
             }
         }
     }
+
+    /// Returns ContentMap as map with normalized BCP-47 language tags
+    /// as keys, see [normalize_language_map()].
+    /// Value of `List` variant is mapped to [UNDETERMINED_LANGUAGE].
+    pub fn as_language_map(&self) -> HashMap<String, &String> {
+        match self {
+            ContentMap::Map(map) => normalize_language_map(
+                map.iter().map(|(k, v)| (k.as_str(), v))
+            ),
+
+            ContentMap::List(list) => list.first()
+                .map(|value| HashMap::from([
+                    (UNDETERMINED_LANGUAGE.to_string(), value)
+                ]))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns `true` if map declares languages rather than just lists value.
+    fn is_declared(&self) -> bool {
+        matches!(self, ContentMap::Map(_))
+    }
+}
+
+/// How content warnings are handled by [Content::get_content_map_with_options()].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ContentWarningMode {
+    /// Content warning is prepended to content as paragraph.
+    #[default]
+    Prepend,
+
+    /// Content warning is dropped.
+    Exclude,
+
+    /// Content warning is returned separately from content.
+    Separate,
+}
+
+/// Options of [Content::get_content_map_with_options()].
+#[derive(Debug, Clone, Default)]
+pub struct ContentMapOptions {
+    /// How content warnings are handled.
+    pub content_warning: ContentWarningMode,
+}
+
+/// Content map built by [Content::get_content_map_with_options()].
+#[derive(Debug, Clone)]
+pub struct ContentMapParts {
+    /// Language to content mapping.
+    pub content: HashMap<String, String>,
+
+    /// Language to content warning mapping, it is filled only if
+    /// content warnings are kept separately.
+    pub content_warnings: HashMap<String, String>,
+}
+
+/// Joins `summary` with `content` as paragraph preceding it
+/// and applies `cleaner` to the result.
+fn join_summary(
+    summary: Option<&String>,
+    content: Option<&String>,
+    cleaner: &dyn Fn(&str) -> String,
+) -> Option<String> {
+    match (summary, content) {
+        (Some(summary), Some(content)) => Some(
+            cleaner(&format!("<p>{summary}</p>\n{content}"))
+        ),
+        (Some(summary), None) => Some(cleaner(summary)),
+        (None, Some(content)) => Some(cleaner(content)),
+        (None, None) => None,
+    }
+}
+
+/// Deserializes optional property not essential to content, e.g. one
+/// of specific software. Malformed values are treated as missing,
+/// so those do not fail the whole content.
+pub(crate) fn deserialize_lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// Returns `totalItems` of embedded collection.
+fn total_items(reference: &Option<CollectionReference>) -> Option<u64> {
+    match reference.as_ref()? {
+        CollectionReference::Collection(collection) => collection.total_items,
+        CollectionReference::Url(_) => None,
+    }
 }
 
 impl Content {
+    /// Returns ID of object this content replies to, if any.
+    pub fn reply_target(&self) -> Option<&url::Url> {
+        self.in_reply_to.as_ref()
+            .map(|reference| reference.object_id())
+    }
+
+    /// Returns number of replies, if reported.
+    pub fn replies_count(&self) -> Option<u64> {
+        total_items(&self.replies)
+    }
+
+    /// Returns number of likes, if reported.
+    pub fn likes_count(&self) -> Option<u64> {
+        total_items(&self.likes)
+    }
+
+    /// Returns number of announces, if reported.
+    pub fn shares_count(&self) -> Option<u64> {
+        total_items(&self.shares)
+    }
+
+    /// Returns replies embedded into `replies` collection or its
+    /// first page, these are not necessarily all replies.
+    pub fn inline_replies(&self) -> Vec<&CollectionItem> {
+        let replies = match &self.replies {
+            Some(CollectionReference::Collection(replies)) => replies,
+            _ => return vec![],
+        };
+
+        let first_page_items = match &replies.first {
+            Some(CollectionReference::Collection(page)) => page.items(),
+            _ => vec![],
+        };
+
+        replies.items()
+            .into_iter()
+            .chain(first_page_items)
+            .collect()
+    }
+
+    /// Returns ID of object this content quotes, if any.
+    ///
+    /// Quotes are expressed in multiple ways, the first found is used:
+    /// - `quoteUrl` property,
+    /// - `_misskey_quote` property,
+    /// - `quoteUri` property,
+    /// - FEP-e232 `Link` tag referencing ActivityPub object.
+    pub fn quoted_object(&self) -> Option<&url::Url> {
+        self.quote_url.as_ref()
+            .or(self.misskey_quote.as_ref())
+            .or(self.quote_uri.as_ref())
+            .or_else(|| self.tag.iter()
+                .flat_map(|reference| reference.as_vec())
+                .filter(|tag| tag.is_object_link())
+                .find_map(|tag| tag.object_id())
+            )
+    }
+
+    /// Returns identifier of conversation this content belongs to.
+    /// `context` property is preferred over OStatus `conversation`.
+    pub fn conversation_id(&self) -> Option<&str> {
+        self.conversation_context.as_ref()
+            .map(|reference| reference.object_id().as_str())
+            .or(self.conversation.as_deref())
+    }
+
     /// Returns content map for Content as language to content mapping.
     /// Content values are cleaned and joined with summary if any.
     /// `cleaner` function is applied to content before wrapping it into
     /// returned value.
+    /// Keys are returned as is, see [Content::get_localized_content_map()]
+    /// for normalized language tags.
     pub fn get_content_map(
         &self,
         cleaner: &dyn Fn(&str) -> String
     ) -> Option<HashMap<String, String>> {
+        self.get_content_map_with_options(cleaner, &ContentMapOptions::default())
+            .map(|parts| parts.content)
+            .filter(|content| !content.is_empty())
+    }
+
+    /// Returns content map for Content as language to content mapping,
+    /// the same as [Content::get_content_map()] does, but handles
+    /// content warnings as `options` say.
+    ///
+    /// Excerpts are always joined with content, see [Content::summary_kind()].
+    pub fn get_content_map_with_options(
+        &self,
+        cleaner: &dyn Fn(&str) -> String,
+        options: &ContentMapOptions,
+    ) -> Option<ContentMapParts> {
+        let content_warning = self.content_warning();
+
+        let (summary, separate_warning) = match (content_warning, options.content_warning) {
+            (Some(_), ContentWarningMode::Exclude) => (None, None),
+            (Some(warning), ContentWarningMode::Separate) => (None, Some(warning)),
+            _ => (self.summary.as_ref(), None),
+        };
+
+        let join = |content: Option<&String>| join_summary(summary, content, cleaner);
+
+        let mut content: HashMap<String, String> = self.content_map.iter()
+            .flat_map(|content_map| content_map.as_map())
+            .filter_map(|(k, v)| join(Some(v))
+                .map(|value| (k.to_string(), value))
+            )
+            .collect();
+
+        if content.is_empty() {
+            let fallback = self.content.as_ref()
+                .or(self.object_entity.name.as_ref());
+
+            if let Some(value) = join(fallback) {
+                content.insert("default".to_string(), value);
+            }
+        }
+
+        let content_warnings: HashMap<String, String> = match separate_warning {
+            None => HashMap::new(),
+
+            Some(warning) if content.is_empty() => HashMap::from([
+                ("default".to_string(), cleaner(warning))
+            ]),
+
+            Some(warning) => content.keys()
+                .map(|language| (language.clone(), cleaner(warning)))
+                .collect(),
+        };
+
+        if content.is_empty() && content_warnings.is_empty() {
+            return None;
+        }
+
+        Some(ContentMapParts {
+            content,
+            content_warnings,
+        })
+    }
+
+    /// Returns content map for Content as normalized language tag to
+    /// localized content mapping.
+    ///
+    /// Unlike [Content::get_content_map()], keys are BCP-47 language tags,
+    /// and each value tells if its language is declared by `contentMap` or
+    /// inferred. Without `contentMap` language is inferred from `summaryMap`
+    /// or `nameMap` if those declare exactly one language, otherwise
+    /// [UNDETERMINED_LANGUAGE] is used.
+    ///
+    /// Summary is joined with content the same way as in
+    /// [Content::get_content_map()], summary in matching language
+    /// from `summaryMap` is preferred.
+    pub fn get_localized_content_map(
+        &self,
+        cleaner: &dyn Fn(&str) -> String
+    ) -> Option<HashMap<String, LocalizedContent>> {
+        let summary_map = self.summary_map.as_ref()
+            .map(|summary_map| summary_map.as_language_map())
+            .unwrap_or_default();
+
+        let localize = |language: &str, content: Option<&String>| {
+            let summary = summary_map.get(language)
+                .copied()
+                .or(self.summary.as_ref());
+
+            join_summary(summary, content, cleaner)
+        };
+
         if let Some(content_map) = &self.content_map {
-            let summary = self.summary.as_ref();
+            let origin = match content_map.is_declared() {
+                true => LanguageOrigin::Declared,
+                false => LanguageOrigin::Inferred,
+            };
 
-            let map: HashMap<String, String> = content_map.as_map()
+            let map: HashMap<String, LocalizedContent> = content_map.as_language_map()
                 .into_iter()
-                .map(|(k, v)|
-                    {
-                        (
-                            k.to_string(),
-                            match summary {
-                                None => cleaner(v),
-
-                                Some(summary) => {
-                                    let joined_value = format!(
-                                        "<p>{summary}</p>\n{v}"
-                                    );
-
-                                    cleaner(&joined_value)
-                                }
-                            }
-                        )
-                    }
-                )
+                .filter_map(|(language, content)| {
+                    let text = localize(&language, Some(content))?;
+                    Some((language, LocalizedContent { text, origin }))
+                })
                 .collect();
 
             if !map.is_empty() {
@@ -177,40 +678,51 @@ impl Content {
             }
         }
 
-        let summary = self.summary.as_ref();
-
         let content = self.content.as_ref()
             .or(self.object_entity.name.as_ref());
 
-        if content.is_some() && summary.is_some() {
-            let text = format!(
-                "<p>{}</p>\n{}",
-                summary.unwrap(), content.unwrap()
-            );
+        let language = self.inferred_language()
+            .unwrap_or_else(|| UNDETERMINED_LANGUAGE.to_string());
 
-            return Some(HashMap::from([
-                (
-                    "default".to_string(),
-                    cleaner(&text)
-                )
-            ]));
-        } else if summary.is_some() {
-            return Some(HashMap::from([
-                (
-                    "default".to_string(),
-                    cleaner(summary.unwrap())
-                )
-            ]));
-        } else if let Some(content) = content {
-            return Some(HashMap::from([
+        localize(&language, content)
+            .map(|text| HashMap::from([
                 (
-                    "default".to_string(),
-                    cleaner(content)
+                    language,
+                    LocalizedContent {
+                        text,
+                        origin: LanguageOrigin::Inferred,
+                    }
                 )
-            ]));
-        }
+            ]))
+    }
+
+    /// Returns language and localized content that suits
+    /// `accept_language`, e.g. value of `Accept-Language` header, best.
+    /// See [crate::language::best_localized()] for details.
+    pub fn get_best_localized_content(
+        &self,
+        accept_language: &str,
+        cleaner: &dyn Fn(&str) -> String
+    ) -> Option<(String, LocalizedContent)> {
+        let mut map = self.get_localized_content_map(cleaner)?;
+
+        let language = best_localized(&map, accept_language)?
+            .0
+            .to_string();
+
+        map.remove_entry(&language)
+    }
 
-        None
+    /// Returns language declared by `summaryMap` or `nameMap`
+    /// if it is the only one declared.
+    fn inferred_language(&self) -> Option<String> {
+        [&self.summary_map, &self.name_map].into_iter()
+            .flatten()
+            .filter(|map| map.is_declared())
+            .map(|map| map.as_language_map())
+            .find(|map| map.len() == 1)
+            .and_then(|map| map.into_keys().next())
+            .filter(|language| language != UNDETERMINED_LANGUAGE)
     }
 
     /// This method returns discoverability state for Content.
@@ -318,10 +830,9 @@ mod tests {
     use language_utils::content_cleaner::clean_some_content;
 
     use crate::content::Content;
+    use crate::language::LanguageOrigin;
 
-    #[test]
-    fn test_object_deserialize_success() {
-        let serialized = r#"{
+    const MISSKEY_NOTE: &str = r#"{
     "id": "https://live-theater.net/notes/xxxxxx",
         "type": "Note",
         "attributedTo": "https://live-theater.net/users/yyyyyyy",
@@ -355,7 +866,137 @@ mod tests {
             }
         ]
     }"#;
-        assert!(serde_json::from_str::<Content>(serialized).is_ok());
+
+    /// Deserializes `serialized` content, serializes it back and checks
+    /// result is stable and keeps all non-null top level properties.
+    /// Returns both original and serialized values for further checks.
+    #[cfg(feature = "unknown_properties")]
+    fn assert_round_trip(serialized: &str) -> (serde_json::Value, serde_json::Value) {
+        let original: serde_json::Value = serde_json::from_str(serialized).unwrap();
+        let content: Content = serde_json::from_value(original.clone()).unwrap();
+        let round_tripped = serde_json::to_value(&content).unwrap();
+
+        let content: Content = serde_json::from_value(round_tripped.clone())
+            .unwrap();
+
+        assert_eq!(round_tripped, serde_json::to_value(&content).unwrap());
+
+        for (key, value) in original.as_object().unwrap() {
+            if value.is_null() {
+                continue;
+            }
+
+            assert!(
+                round_tripped.get(key).is_some(),
+                "property '{key}' is lost in round-trip"
+            );
+        }
+
+        (original, round_tripped)
+    }
+
+    #[test]
+    #[cfg(feature = "unknown_properties")]
+    fn test_misskey_note_round_trip() {
+        let (original, round_tripped) = assert_round_trip(MISSKEY_NOTE);
+
+        for key in ["_misskey_content", "source", "cc"] {
+            assert_eq!(original[key], round_tripped[key]);
+        }
+
+        assert_eq!(original["tag"][0]["updated"], round_tripped["tag"][0]["updated"]);
+        assert_eq!("Image", round_tripped["tag"][0]["icon"]["type"]);
+        assert_eq!("image/png", round_tripped["tag"][0]["icon"]["mediaType"]);
+    }
+
+    #[test]
+    #[cfg(feature = "unknown_properties")]
+    fn test_mastodon_note_round_trip() {
+        let (original, round_tripped) = assert_round_trip(r##"{
+          "@context": [
+            "https://www.w3.org/ns/activitystreams",
+            {
+              "ostatus": "http://ostatus.org#",
+              "atomUri": "ostatus:atomUri",
+              "conversation": "ostatus:conversation",
+              "sensitive": "as:sensitive",
+              "toot": "http://joinmastodon.org/ns#",
+              "blurhash": "toot:blurhash",
+              "focalPoint": {"@container": "@list", "@id": "toot:focalPoint"}
+            }
+          ],
+          "id": "https://z.y.x/users/xyz/statuses/123456789",
+          "type": "Note",
+          "summary": null,
+          "inReplyTo": null,
+          "published": "2024-01-22T13:37:04Z",
+          "url": "https://z.y.x/@xyz/123456789",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "to": ["https://www.w3.org/ns/activitystreams#Public"],
+          "cc": ["https://z.y.x/users/xyz/followers"],
+          "sensitive": false,
+          "atomUri": "https://z.y.x/users/xyz/statuses/123456789",
+          "conversation": "tag:z.y.x,2024-01-22:objectId=1:objectType=Conversation",
+          "content": "<p>Cat <a href=\"https://z.y.x/tags/cat\" class=\"mention hashtag\" rel=\"tag\">#<span>cat</span></a></p>",
+          "contentMap": {
+            "en": "<p>Cat <a href=\"https://z.y.x/tags/cat\" class=\"mention hashtag\" rel=\"tag\">#<span>cat</span></a></p>"
+          },
+          "attachment": [
+            {
+              "type": "Document",
+              "mediaType": "image/jpeg",
+              "url": "https://files.z.y.x/media_attachments/files/original/cat.jpg",
+              "name": "A cat sleeping on keyboard",
+              "blurhash": "UBL_:rOpGG-oBUNG,qRj2so|=eE1w^n4S5NH",
+              "focalPoint": [0.0, 0.5],
+              "width": 1200,
+              "height": 800
+            }
+          ],
+          "tag": [
+            {
+              "type": "Hashtag",
+              "href": "https://z.y.x/tags/cat",
+              "name": "#cat"
+            }
+          ],
+          "replies": {
+            "id": "https://z.y.x/users/xyz/statuses/123456789/replies",
+            "type": "Collection",
+            "first": {
+              "type": "CollectionPage",
+              "next": "https://z.y.x/users/xyz/statuses/123456789/replies?only_other_accounts=true&page=true",
+              "partOf": "https://z.y.x/users/xyz/statuses/123456789/replies",
+              "items": []
+            }
+          }
+        }"##);
+
+        for key in ["atomUri", "conversation", "replies", "@context"] {
+            assert_eq!(original[key], round_tripped[key]);
+        }
+
+        let attachment = &round_tripped["attachment"][0];
+
+        assert_eq!(original["attachment"][0]["blurhash"], attachment["blurhash"]);
+        assert_eq!(original["attachment"][0]["focalPoint"], attachment["focalPoint"]);
+        assert_eq!("image/jpeg", attachment["mediaType"]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "unknown_properties"))]
+    fn test_unknown_properties_are_not_kept_by_default() {
+        let content: Content = serde_json::from_str(MISSKEY_NOTE).unwrap();
+        let round_tripped = serde_json::to_value(&content).unwrap();
+
+        assert!(content.object_entity.entity.unknown_properties.is_empty());
+        assert!(round_tripped["tag"][0]["icon"].get("type").is_none());
+        assert_eq!("image/png", round_tripped["tag"][0]["icon"]["mediaType"]);
+    }
+
+    #[test]
+    fn test_object_deserialize_success() {
+        assert!(serde_json::from_str::<Content>(MISSKEY_NOTE).is_ok());
     }
 
     #[test]
@@ -398,7 +1039,138 @@ mod tests {
         }
         "#;
 
-        assert!(serde_json::from_str::<Content>(serialized).is_ok());
+        let content = serde_json::from_str::<Content>(serialized).unwrap();
+
+        assert_eq!(
+            "https://site.domain/notes/note123",
+            content.reply_target().unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn test_quoted_object_is_found() {
+        let serialized = r#"{
+          "id": "https://misskey.x.y/notes/quoting",
+          "type": "Note",
+          "attributedTo": "https://misskey.x.y/users/xyz",
+          "content": "<p>Look at this</p>",
+          "published": "2024-04-24T14:41:47Z",
+          "context": "https://misskey.x.y/contexts/abc",
+          "tag": [
+            {
+              "type": "Link",
+              "mediaType": "application/ld+json; profile=\"https://www.w3.org/ns/activitystreams\"",
+              "href": "https://z.y.x/users/abc/statuses/1",
+              "name": "RE: https://z.y.x/users/abc/statuses/1"
+            }
+          ]
+        }"#;
+
+        let content: Content = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(
+            "https://z.y.x/users/abc/statuses/1",
+            content.quoted_object().unwrap().as_str()
+        );
+
+        assert_eq!(
+            Some("https://misskey.x.y/contexts/abc"),
+            content.conversation_id()
+        );
+
+        assert!(content.reply_target().is_none());
+
+        let mut value: serde_json::Value = serde_json::from_str(serialized).unwrap();
+        value["_misskey_quote"] = "https://misskey.x.y/notes/quoted".into();
+
+        let content: Content = serde_json::from_value(value).unwrap();
+
+        assert_eq!(
+            "https://misskey.x.y/notes/quoted",
+            content.quoted_object().unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn test_engagement_collections() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "content": "<p>Meow</p>",
+          "published": "2024-01-01T00:00:00Z",
+          "replies": {
+            "id": "https://z.y.x/users/xyz/statuses/1/replies",
+            "type": "Collection",
+            "first": {
+              "type": "CollectionPage",
+              "next": "https://z.y.x/users/xyz/statuses/1/replies?page=true",
+              "partOf": "https://z.y.x/users/xyz/statuses/1/replies",
+              "items": ["https://z.y.x/users/xyz/statuses/2"]
+            }
+          },
+          "likes": {
+            "id": "https://z.y.x/users/xyz/statuses/1/likes",
+            "type": "Collection",
+            "totalItems": 5
+          },
+          "shares": "https://z.y.x/users/xyz/statuses/1/shares"
+        }"#).unwrap();
+
+        assert_eq!(None, content.replies_count());
+        assert_eq!(Some(5), content.likes_count());
+        assert_eq!(None, content.shares_count());
+
+        let replies = content.inline_replies();
+
+        assert_eq!(1, replies.len());
+        assert_eq!("https://z.y.x/users/xyz/statuses/2", replies[0].id().unwrap().as_str());
+    }
+
+    #[test]
+    fn test_malformed_engagement_collections_are_ignored() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "content": "<p>Meow</p>",
+          "published": "2024-01-01T00:00:00Z",
+          "replies": {
+            "id": "https://z.y.x/users/xyz/statuses/1/replies",
+            "totalItems": 2
+          },
+          "likes": {
+            "type": "Collection",
+            "totalItems": "3"
+          },
+          "shares": 7
+        }"#).unwrap();
+
+        assert!(content.replies.is_none());
+        assert_eq!(None, content.likes_count());
+        assert!(content.shares.is_none());
+        assert!(content.inline_replies().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_references_are_ignored() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "content": "<p>Meow</p>",
+          "published": "2024-01-01T00:00:00Z",
+          "inReplyTo": "",
+          "context": 1,
+          "conversation": {},
+          "quoteUrl": "not a url",
+          "_misskey_quote": "https://misskey.x.y/notes/quoted"
+        }"#).unwrap();
+
+        assert!(content.reply_target().is_none());
+        assert!(content.conversation_context.is_none());
+        assert!(content.conversation.is_none());
+        assert_eq!(Some("https://misskey.x.y/notes/quoted"), content.quoted_object().map(|url| url.as_str()));
     }
 
     #[test]
@@ -430,4 +1202,60 @@ mod tests {
             "Text&lt;&lt;&gt;br /&gt;<a href=\"https://www.xyz.net/x/y/z/\" rel=\"noopener noreferrer\"></a>"
         )
     }
+    #[test]
+    fn test_localized_content_map() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "summary": "CW",
+          "summaryMap": {"DE_de": "Inhaltswarnung"},
+          "content": "<p>Hallo</p>",
+          "contentMap": {"DE_de": "<p>Hallo</p>", "en": "<p>Hello</p>"}
+        }"#).unwrap();
+
+        let cleaner = |v: &str| v.to_string();
+        let map = content.get_localized_content_map(&cleaner).unwrap();
+
+        let german = map.get("de-DE").unwrap();
+
+        assert_eq!(LanguageOrigin::Declared, german.origin);
+        assert_eq!("<p>Inhaltswarnung</p>\n<p>Hallo</p>", german.text);
+        assert_eq!("<p>CW</p>\n<p>Hello</p>", map.get("en").unwrap().text);
+
+        let (language, _) = content.get_best_localized_content("de-AT, en;q=0.5", &cleaner)
+            .unwrap();
+
+        assert_eq!("de-DE", language);
+
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/2",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "content": "<p>Bonjour</p>",
+          "nameMap": {"fr": "Salut"}
+        }"#).unwrap();
+
+        let map = content.get_localized_content_map(&cleaner).unwrap();
+
+        assert_eq!(LanguageOrigin::Inferred, map.get("fr").unwrap().origin);
+    }
+
+    #[test]
+    fn test_malformed_localized_maps_are_ignored() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "content": "<p>Hello</p>",
+          "summaryMap": {"en": ["CW"]},
+          "nameMap": "Title"
+        }"#).unwrap();
+
+        assert!(content.summary_map.is_none());
+        assert!(content.name_map.is_none());
+    }
 }
\ No newline at end of file
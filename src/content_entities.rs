@@ -0,0 +1,465 @@
+use crate::actor::ActorReadableId;
+use crate::content::Content;
+use crate::entity::EntityType;
+use crate::html::SpanKind;
+use crate::image::ImageReference;
+use crate::object_guesser::extract_actor_readable_id_from_url;
+use crate::tag::Tag;
+
+/// Where entity is found.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EntitySource {
+    /// Only in content HTML.
+    Html,
+
+    /// Only in `tag` list.
+    Tags,
+
+    /// Both in content HTML and `tag` list.
+    Both,
+}
+
+impl EntitySource {
+    /// Returns source of entity that is found in `other` source as well.
+    fn merge(self, other: EntitySource) -> Self {
+        if self == other {
+            self
+        } else {
+            EntitySource::Both
+        }
+    }
+}
+
+/// Mention of actor.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mention {
+    /// Mention as written, e.g. `@user@host` or just `@user`.
+    pub name: String,
+
+    /// Actor URL, if known. `tag` list is preferred over HTML as HTML
+    /// usually links profile page rather than actor.
+    pub actor_url: Option<url::Url>,
+
+    /// Actor as username and server, if it is possible to infer.
+    pub readable_id: Option<ActorReadableId>,
+
+    /// Where mention is found.
+    pub source: EntitySource,
+}
+
+/// Hashtag.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Hashtag {
+    /// Normalized name: without leading `#` and lowercased.
+    pub name: String,
+
+    /// Link to hashtag page, if any.
+    pub url: Option<url::Url>,
+
+    /// Where hashtag is found.
+    pub source: EntitySource,
+}
+
+/// Custom emoji.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CustomEmoji {
+    /// Shortcode without colons, e.g. `blobcat`.
+    pub shortcode: String,
+
+    /// Image to display for emoji, if any.
+    pub image_url: Option<url::Url>,
+
+    /// Where emoji is found.
+    pub source: EntitySource,
+}
+
+/// Mentions, hashtags and custom emoji found in content.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ContentEntities {
+    /// Mentions in order of appearance.
+    pub mentions: Vec<Mention>,
+
+    /// Hashtags in order of appearance.
+    pub hashtags: Vec<Hashtag>,
+
+    /// Custom emoji in order of appearance.
+    pub emojis: Vec<CustomEmoji>,
+}
+
+/// Normalizes hashtag name: strips leading `#` and lowercases it.
+pub fn normalize_hashtag(name: &str) -> String {
+    name.trim()
+        .trim_start_matches('#')
+        .to_lowercase()
+}
+
+/// Normalizes emoji shortcode: strips surrounding colons.
+pub(crate) fn normalize_shortcode(name: &str) -> String {
+    name.trim()
+        .trim_matches(':')
+        .to_string()
+}
+
+/// Parses `value` as URL, empty or invalid values are ignored.
+fn parse_url(value: Option<&str>) -> Option<url::Url> {
+    value.and_then(|value| url::Url::parse(value).ok())
+}
+
+/// Returns `true` if `html_name` mention, e.g. `@user`, refers the same
+/// actor as `tag_name` mention, e.g. `@user@host`.
+fn mention_names_match(html_name: &str, tag_name: &str) -> bool {
+    let html_name = html_name.trim_start_matches('@').to_lowercase();
+    let tag_name = tag_name.trim_start_matches('@').to_lowercase();
+
+    if html_name == tag_name {
+        return true;
+    }
+
+    // local mentions are rendered without server part in HTML.
+    !html_name.contains('@') &&
+        tag_name.split_once('@')
+            .map(|(username, _)| username == html_name)
+            .unwrap_or(false)
+}
+
+/// Returns `true` if mentions could refer the same actor judging by hosts
+/// of their actor URLs or, if only one URL is known, by host of handle.
+/// Actor URL in HTML is often profile page rather than ID, so URLs are
+/// not compared as a whole.
+fn mention_hosts_match(
+    left_name: &str,
+    left_url: Option<&url::Url>,
+    right_name: &str,
+    right_url: Option<&url::Url>,
+) -> bool {
+    let handle_host = |name: &str| name.trim_start_matches('@')
+        .split_once('@')
+        .map(|(_, host)| host.to_lowercase());
+
+    match (left_url, right_url) {
+        (Some(left), Some(right)) => left.host_str() == right.host_str(),
+
+        (Some(url), None) => handle_host(right_name)
+            .map(|host| url.host_str() == Some(host.as_str()))
+            .unwrap_or(true),
+
+        (None, Some(url)) => handle_host(left_name)
+            .map(|host| url.host_str() == Some(host.as_str()))
+            .unwrap_or(true),
+
+        (None, None) => true,
+    }
+}
+
+/// Returns image URL of emoji tag.
+pub(crate) fn emoji_image_url(tag: &Tag) -> Option<url::Url> {
+    match tag.icon.as_ref()? {
+        ImageReference::Url(url) => Some(url.clone()),
+        ImageReference::Single(image) => image.url.clone(),
+        ImageReference::List(images) => images.iter()
+            .find_map(|image| image.url.clone()),
+    }
+}
+
+impl Content {
+    /// Returns mentions, hashtags and custom emoji reconciled from
+    /// content HTML and `tag` list.
+    ///
+    /// These two sources often disagree: tags could be missing, have
+    /// empty `href`, or HTML could be rendered differently by Hubzilla,
+    /// Friendica and others. Entities found in both are merged, each is
+    /// marked with [EntitySource] it is found in.
+    pub fn entities(&self) -> ContentEntities {
+        let mut entities = ContentEntities::default();
+
+        if let Some(extracted) = self.extract_text() {
+            for span in extracted.spans.iter() {
+                let text = span.text(&extracted.text);
+
+                match span.kind {
+                    SpanKind::Mention => entities.add_mention(
+                        text,
+                        parse_url(span.href.as_deref()),
+                        EntitySource::Html,
+                    ),
+
+                    SpanKind::Hashtag => entities.add_hashtag(
+                        text,
+                        parse_url(span.href.as_deref()),
+                        EntitySource::Html,
+                    ),
+
+                    SpanKind::Emoji => entities.add_emoji(
+                        text,
+                        parse_url(span.href.as_deref()),
+                        EntitySource::Html,
+                    ),
+
+                    SpanKind::Link => {}
+                }
+            }
+        }
+
+        let tags: Vec<&Tag> = self.tag.iter()
+            .flat_map(|reference| reference.as_vec())
+            .collect();
+
+        for tag in tags.into_iter() {
+            let name = match tag.name.as_deref() {
+                None => continue,
+                Some(name) => name,
+            };
+
+            match tag.entity_type() {
+                EntityType::Mention => entities.add_mention(
+                    name,
+                    tag.object_id().cloned(),
+                    EntitySource::Tags,
+                ),
+
+                EntityType::Hashtag |
+                EntityType::Tag => entities.add_hashtag(
+                    name,
+                    tag.object_id().cloned(),
+                    EntitySource::Tags,
+                ),
+
+                EntityType::Emoji => entities.add_emoji(
+                    name,
+                    emoji_image_url(tag),
+                    EntitySource::Tags,
+                ),
+
+                _ => {}
+            }
+        }
+
+        for mention in entities.mentions.iter_mut() {
+            if mention.readable_id.is_none() {
+                mention.readable_id = ActorReadableId::from_handle(&mention.name)
+                    .or_else(|| mention.actor_url.as_ref()
+                        .and_then(extract_actor_readable_id_from_url)
+                    );
+            }
+        }
+
+        entities
+    }
+}
+
+impl ContentEntities {
+    fn add_mention(
+        &mut self,
+        name: &str,
+        actor_url: Option<url::Url>,
+        source: EntitySource,
+    ) {
+        let name = name.trim();
+
+        let existing = self.mentions.iter_mut()
+            .find(|mention| {
+                let names_match = mention_names_match(&mention.name, name) ||
+                    mention_names_match(name, &mention.name);
+
+                let hosts_match = mention_hosts_match(
+                    &mention.name,
+                    mention.actor_url.as_ref(),
+                    name,
+                    actor_url.as_ref(),
+                );
+
+                (names_match && hosts_match) ||
+                    (actor_url.is_some() && mention.actor_url == actor_url)
+            });
+
+        match existing {
+            Some(mention) => {
+                mention.source = mention.source.merge(source);
+
+                // full handle and actor URL from tags win.
+                if source == EntitySource::Tags {
+                    if name.len() > mention.name.len() {
+                        mention.name = name.to_string();
+                    }
+
+                    if actor_url.is_some() {
+                        mention.actor_url = actor_url;
+                    }
+                } else if mention.actor_url.is_none() {
+                    mention.actor_url = actor_url;
+                }
+            }
+
+            None => self.mentions.push(Mention {
+                name: name.to_string(),
+                actor_url,
+                readable_id: None,
+                source,
+            }),
+        }
+    }
+
+    fn add_hashtag(
+        &mut self,
+        name: &str,
+        url: Option<url::Url>,
+        source: EntitySource,
+    ) {
+        let name = normalize_hashtag(name);
+
+        if name.is_empty() {
+            return;
+        }
+
+        match self.hashtags.iter_mut().find(|hashtag| hashtag.name == name) {
+            Some(hashtag) => {
+                hashtag.source = hashtag.source.merge(source);
+
+                if hashtag.url.is_none() {
+                    hashtag.url = url;
+                }
+            }
+
+            None => self.hashtags.push(Hashtag {
+                name,
+                url,
+                source,
+            }),
+        }
+    }
+
+    fn add_emoji(
+        &mut self,
+        name: &str,
+        image_url: Option<url::Url>,
+        source: EntitySource,
+    ) {
+        let shortcode = normalize_shortcode(name);
+
+        if shortcode.is_empty() {
+            return;
+        }
+
+        match self.emojis.iter_mut().find(|emoji| emoji.shortcode == shortcode) {
+            Some(emoji) => {
+                emoji.source = emoji.source.merge(source);
+
+                if emoji.image_url.is_none() {
+                    emoji.image_url = image_url;
+                }
+            }
+
+            None => self.emojis.push(CustomEmoji {
+                shortcode,
+                image_url,
+                source,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::Content;
+    use crate::content_entities::EntitySource;
+
+    #[test]
+    fn test_entities_are_reconciled() {
+        let content: Content = serde_json::from_str(r##"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "content": "<p><span class=\"h-card\"><a href=\"https://z.y.x/@cat\" class=\"u-url mention\">@<span>cat</span></a></span> loves <a href=\"https://z.y.x/tags/Boxes\" class=\"mention hashtag\" rel=\"tag\">#<span>Boxes</span></a> :blobcat:</p>",
+          "tag": [
+            {
+              "type": "Mention",
+              "href": "https://z.y.x/users/cat",
+              "name": "@cat@z.y.x"
+            },
+            {
+              "type": "Mention",
+              "href": "",
+              "name": "@dog@b.chat"
+            },
+            {
+              "type": "Hashtag",
+              "href": "https://z.y.x/tags/boxes",
+              "name": "#boxes"
+            },
+            {
+              "type": "Emoji",
+              "name": ":blobcat:",
+              "icon": {
+                "type": "Image",
+                "url": "https://z.y.x/emoji/blobcat.png"
+              }
+            }
+          ]
+        }"##).unwrap();
+
+        let entities = content.entities();
+
+        assert_eq!(2, entities.mentions.len());
+
+        let cat = &entities.mentions[0];
+
+        assert_eq!("@cat@z.y.x", cat.name);
+        assert_eq!(EntitySource::Both, cat.source);
+        assert_eq!("https://z.y.x/users/cat", cat.actor_url.as_ref().unwrap().as_str());
+        assert_eq!("z.y.x", cat.readable_id.as_ref().unwrap().server);
+
+        let dog = &entities.mentions[1];
+
+        assert_eq!(EntitySource::Tags, dog.source);
+        assert!(dog.actor_url.is_none());
+        assert_eq!("dog", dog.readable_id.as_ref().unwrap().username);
+
+        assert_eq!(1, entities.hashtags.len());
+        assert_eq!("boxes", entities.hashtags[0].name);
+        assert_eq!(EntitySource::Both, entities.hashtags[0].source);
+
+        assert_eq!(1, entities.emojis.len());
+        assert_eq!("blobcat", entities.emojis[0].shortcode);
+        assert_eq!(EntitySource::Both, entities.emojis[0].source);
+
+        assert_eq!(
+            "https://z.y.x/emoji/blobcat.png",
+            entities.emojis[0].image_url.as_ref().unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn test_mentions_of_namesakes_are_not_merged() {
+        let content: Content = serde_json::from_str(r##"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "content": "<p><a href=\"https://c.d/users/cat\" class=\"u-url mention\">@<span>cat</span></a> meets <a href=\"https://a.b/@cat\" class=\"u-url mention\">@<span>cat</span></a></p>",
+          "tag": [
+            {
+              "type": "Mention",
+              "href": "https://a.b/users/cat",
+              "name": "@cat@a.b"
+            }
+          ]
+        }"##).unwrap();
+
+        let entities = content.entities();
+
+        assert_eq!(2, entities.mentions.len());
+
+        let remote = &entities.mentions[0];
+
+        assert_eq!("@cat", remote.name);
+        assert_eq!(EntitySource::Html, remote.source);
+        assert_eq!("https://c.d/users/cat", remote.actor_url.as_ref().unwrap().as_str());
+
+        let tagged = &entities.mentions[1];
+
+        assert_eq!("@cat@a.b", tagged.name);
+        assert_eq!(EntitySource::Both, tagged.source);
+        assert_eq!("https://a.b/users/cat", tagged.actor_url.as_ref().unwrap().as_str());
+    }
+}
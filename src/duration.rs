@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Helper enumeration to deal with different types of `duration` property.
+///
+/// ActivityStreams defines it as `xsd:duration`, e.g. `PT2M13S` as PeerTube
+/// sends it, yet some software, e.g. Funkwhale, sends number of seconds.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DurationValue {
+    /// Number of seconds.
+    Seconds(f64),
+
+    /// ISO 8601 duration, e.g. `PT1H2M3S`.
+    Text(String),
+}
+
+impl DurationValue {
+    /// Returns duration if value is valid, negative durations are rejected.
+    pub fn to_duration(&self) -> Option<Duration> {
+        match self {
+            DurationValue::Seconds(seconds) => seconds_to_duration(*seconds),
+
+            DurationValue::Text(text) => parse_iso8601_duration(text)
+                .or_else(|| text.trim().parse::<f64>().ok()
+                    .and_then(seconds_to_duration)
+                ),
+        }
+    }
+}
+
+/// Converts `seconds` to duration, negative, not finite
+/// and overflowing values are rejected.
+fn seconds_to_duration(seconds: f64) -> Option<Duration> {
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Parses ISO 8601 duration, e.g. `PT2M13S` or `P1DT12H`.
+///
+/// Calendar units are not exact, so a year is counted as 365 days
+/// and a month as 30 days.
+pub fn parse_iso8601_duration(value: &str) -> Option<Duration> {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+
+    let value = value.trim().strip_prefix('P')?;
+
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    let mut time_part = false;
+    let mut has_components = false;
+
+    for c in value.chars() {
+        if c.is_ascii_digit() || c == '.' || c == ',' {
+            number.push(if c == ',' { '.' } else { c });
+            continue;
+        }
+
+        if c == 'T' {
+            if time_part || !number.is_empty() {
+                return None;
+            }
+
+            time_part = true;
+            continue;
+        }
+
+        let unit = match (time_part, c) {
+            (false, 'Y') => 365.0 * DAY,
+            (false, 'M') => 30.0 * DAY,
+            (false, 'W') => 7.0 * DAY,
+            (false, 'D') => DAY,
+            (true, 'H') => HOUR,
+            (true, 'M') => MINUTE,
+            (true, 'S') => 1.0,
+            _ => return None,
+        };
+
+        seconds += number.parse::<f64>().ok()? * unit;
+        number.clear();
+        has_components = true;
+    }
+
+    if !number.is_empty() || !has_components {
+        return None;
+    }
+
+    seconds_to_duration(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::duration::{DurationValue, parse_iso8601_duration};
+
+    #[test]
+    fn test_parse_iso8601_duration() {
+        assert_eq!(Some(Duration::from_secs(133)), parse_iso8601_duration("PT2M13S"));
+        assert_eq!(Some(Duration::from_secs(129_600)), parse_iso8601_duration("P1DT12H"));
+        assert_eq!(Some(Duration::from_millis(1500)), parse_iso8601_duration("PT1.5S"));
+        assert_eq!(None, parse_iso8601_duration("P"));
+        assert_eq!(None, parse_iso8601_duration("PT"));
+        assert_eq!(None, parse_iso8601_duration("PT5"));
+        assert_eq!(None, parse_iso8601_duration("2M13S"));
+
+        let value: DurationValue = serde_json::from_str("42.5").unwrap();
+        assert_eq!(Some(Duration::from_millis(42_500)), value.to_duration());
+
+        let value: DurationValue = serde_json::from_str(r#""PT1H""#).unwrap();
+        assert_eq!(Some(Duration::from_secs(3600)), value.to_duration());
+
+        // remote values must not overflow Duration
+        assert_eq!(None, DurationValue::Seconds(1e20).to_duration());
+        assert_eq!(None, DurationValue::Seconds(-1.0).to_duration());
+        assert_eq!(None, DurationValue::Seconds(f64::NAN).to_duration());
+        assert_eq!(None, parse_iso8601_duration("P99999999999999Y"));
+    }
+}
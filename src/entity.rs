@@ -43,6 +43,10 @@ pub enum EntityType {
     Update,
     View,
 
+    // Non-standard activities.
+    //  Pleroma, Akkoma and Misskey send emoji reactions as EmojiReact.
+    EmojiReact,
+
     // Actors
     Actor,
     Application,
@@ -59,11 +63,14 @@ pub enum EntityType {
 
     // Content
     Article,
+    Audio,
+    Event,
     Image,
     Link,
     Movie,
     Note,
     Page,
+    Place,
     Poll,
     Question,
     Tombstone,
@@ -116,21 +123,74 @@ pub fn is_supported_content_type(entity_type: EntityType) -> bool {
         EntityType::Note |
         EntityType::Video |
         EntityType::Movie |
-        EntityType::Article
+        EntityType::Article |
+        EntityType::Event
     )
 }
 
+/// Properties not modelled by this crate.
+///
+/// These are kept only if `unknown_properties` feature is enabled,
+/// so deserialized documents could be serialized back without loss.
+/// Otherwise they are dropped once parsed. Note that parsing still
+/// pays for them: as the field is flattened, serde buffers every property
+/// of the embedding structure, unknown ones included, before discarding
+/// those. Disabling the feature saves memory of parsed documents only.
+///
+/// Every structure embedded into round-trippable documents, e.g. poll
+/// options of [Content](crate::content::Content), has such field, so
+/// nested properties are not lost either. Views and values computed
+/// by this crate do not.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct UnknownProperties {
+    /// Property name to value mapping.
+    #[cfg(feature = "unknown_properties")]
+    #[serde(flatten)]
+    pub properties: std::collections::HashMap<String, serde_json::Value>,
+}
+
+impl UnknownProperties {
+    /// Returns value of property `name`, if it is kept.
+    pub fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        #[cfg(feature = "unknown_properties")]
+        return self.properties.get(name);
+
+        #[cfg(not(feature = "unknown_properties"))]
+        {
+            let _ = name;
+            None
+        }
+    }
+
+    /// Returns `true` if no properties are kept.
+    pub fn is_empty(&self) -> bool {
+        #[cfg(feature = "unknown_properties")]
+        return self.properties.is_empty();
+
+        #[cfg(not(feature = "unknown_properties"))]
+        true
+    }
+}
+
 /// The most basic ActivityPub data entity in this crate.
 /// It is not actually part of spec but exists here for convenience.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Entity {
     /// Maps to `@context` property that defines schema of document.
     #[serde(rename = "@context")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<Context>,
 
     /// Type of ActivityPub object to figure out how to deal with it.
     #[serde(rename = "type")]
     pub object_type: EntityType,
+
+    /// Properties not modelled by this crate.
+    /// As [Entity] is embedded into other data entities, it collects
+    /// whatever properties embedding entities do not know about,
+    /// see [UnknownProperties].
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
 }
 
 impl Entity {
@@ -148,6 +208,7 @@ impl Entity {
                 ])
             ),
             object_type: entity_type,
+            unknown_properties: UnknownProperties::default(),
         }
     }
 
@@ -157,6 +218,7 @@ impl Entity {
         Self {
             context: Some(context),
             object_type: entity_type,
+            unknown_properties: UnknownProperties::default(),
         }
     }
 }
@@ -173,6 +235,41 @@ pub fn is_actor_type(entity_type: EntityType) -> bool {
     )
 }
 
+/// Returns `true` if `entity_type` is one of activity types.
+pub fn is_activity_type(entity_type: EntityType) -> bool {
+    matches!(
+        entity_type,
+        EntityType::Add |
+        EntityType::Accept |
+        EntityType::Announce |
+        EntityType::Arrive |
+        EntityType::Block |
+        EntityType::Create |
+        EntityType::Delete |
+        EntityType::Dislike |
+        EntityType::Flag |
+        EntityType::Follow |
+        EntityType::Ignore |
+        EntityType::Invite |
+        EntityType::Join |
+        EntityType::Leave |
+        EntityType::Like |
+        EntityType::Listen |
+        EntityType::Move |
+        EntityType::Offer |
+        EntityType::Read |
+        EntityType::Reject |
+        EntityType::Remove |
+        EntityType::TentativeAccept |
+        EntityType::TentativeReject |
+        EntityType::Travel |
+        EntityType::Undo |
+        EntityType::Update |
+        EntityType::View |
+        EntityType::EmojiReact
+    )
+}
+
 /// Converts string `value` to [EntityType] if it matches one of
 /// supported content or actors types.
 pub fn entity_type_from(value: &str) -> EntityType {
@@ -187,9 +284,12 @@ pub fn entity_type_from(value: &str) -> EntityType {
 
         // Content
         "Article" => EntityType::Article,
+        "Audio" => EntityType::Audio,
+        "Event" => EntityType::Event,
         "Image" => EntityType::Image,
-        "Movie" => EntityType::Page,
+        "Movie" => EntityType::Movie,
         "Note" => EntityType::Note,
+        "Page" => EntityType::Page,
         "Poll" => EntityType::Poll,
         "Question" => EntityType::Question,
         "Tombstone" => EntityType::Tombstone,
@@ -0,0 +1,344 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::content::Content;
+use crate::entity::{EntityType, UnknownProperties};
+use crate::object::ObjectTrait;
+
+/// Postal address as defined by schema.org.
+/// See: <https://schema.org/PostalAddress>
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PostalAddress {
+    /// Street address, e.g. `10 Downing Street`.
+    #[serde(rename = "streetAddress")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub street_address: Option<String>,
+
+    /// City or other locality.
+    #[serde(rename = "addressLocality")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_locality: Option<String>,
+
+    /// Region, e.g. state or province.
+    #[serde(rename = "addressRegion")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_region: Option<String>,
+
+    /// Postal code.
+    #[serde(rename = "postalCode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postal_code: Option<String>,
+
+    /// Country, either name or ISO 3166-1 code.
+    #[serde(rename = "addressCountry")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_country: Option<String>,
+
+    /// Properties not modelled by this crate, e.g. `type`.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+impl PostalAddress {
+    /// Returns address as single line, empty parts are skipped.
+    pub fn to_text(&self) -> String {
+        [
+            &self.street_address,
+            &self.postal_code,
+            &self.address_locality,
+            &self.address_region,
+            &self.address_country,
+        ]
+            .into_iter()
+            .flatten()
+            .map(|part| part.trim())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join(", ")
+    }
+}
+
+/// Helper enumeration to deal with different types of `address` property.
+/// Mobilizon sends [PostalAddress], Gancio sends plain text.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum PlaceAddress {
+    Postal(PostalAddress),
+    Text(String),
+}
+
+impl PlaceAddress {
+    /// Returns address as single line.
+    pub fn to_text(&self) -> String {
+        match self {
+            PlaceAddress::Postal(address) => address.to_text(),
+            PlaceAddress::Text(text) => text.trim().to_string(),
+        }
+    }
+}
+
+/// Physical location.
+/// See: <https://www.w3.org/TR/activitystreams-vocabulary/#dfn-place>
+///
+/// Example:
+/// ```json
+/// "location": {
+///   "type": "Place",
+///   "name": "Town hall",
+///   "address": {
+///     "type": "PostalAddress",
+///     "streetAddress": "1 Main Street",
+///     "addressLocality": "Springfield",
+///     "postalCode": "12345",
+///     "addressCountry": "US"
+///   },
+///   "latitude": 39.78,
+///   "longitude": -89.65
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Place {
+    /// Type of location, usually `Place`.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_type: Option<EntityType>,
+
+    /// Place identifier, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<url::Url>,
+
+    /// Name of place, e.g. venue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Address of place.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<PlaceAddress>,
+
+    /// Latitude in degrees.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+
+    /// Longitude in degrees.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+
+    /// Properties not modelled by this crate.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+impl Place {
+    /// Returns latitude and longitude if both are set and valid.
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        let latitude = self.latitude.filter(|value| (-90.0..=90.0).contains(value))?;
+        let longitude = self.longitude.filter(|value| (-180.0..=180.0).contains(value))?;
+
+        Some((latitude, longitude))
+    }
+}
+
+/// How people could join event, as Mobilizon defines it.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum JoinMode {
+    /// Anyone could join.
+    Free,
+
+    /// Participation needs approval by organizer.
+    Restricted,
+
+    /// Participation is by invitation only.
+    Invite,
+
+    /// Participation is managed by external service.
+    External,
+
+    /// Mode this crate does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Status of event, as iCalendar defines it.
+/// See: <https://www.rfc-editor.org/rfc/rfc5545#section-3.8.1.11>
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum EventStatus {
+    /// Event is not confirmed yet.
+    Tentative,
+
+    /// Event is going to take place.
+    Confirmed,
+
+    /// Event is called off.
+    Cancelled,
+
+    /// Status this crate does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Typed view of `Event` object, as Mobilizon and Gancio send it.
+///
+/// Event properties are part of [Content], e.g. `startTime`
+/// and `endTime`, this view interprets them.
+#[derive(Debug, Clone)]
+pub struct Event<'a> {
+    /// Event itself.
+    pub content: &'a Content,
+}
+
+impl<'a> Event<'a> {
+    /// Returns point of time event starts.
+    pub fn start_time(&self) -> Option<DateTime<Utc>> {
+        self.content.start_time
+    }
+
+    /// Returns point of time event ends.
+    pub fn end_time(&self) -> Option<DateTime<Utc>> {
+        self.content.end_time
+    }
+
+    /// Returns IANA time zone of event, e.g. `Europe/Paris`.
+    pub fn timezone(&self) -> Option<&'a str> {
+        self.content.timezone.as_deref()
+    }
+
+    /// Returns place event takes place at.
+    pub fn location(&self) -> Option<&'a Place> {
+        self.content.location.as_ref()
+    }
+
+    /// Returns how people could join event.
+    pub fn join_mode(&self) -> Option<JoinMode> {
+        self.content.join_mode
+    }
+
+    /// Returns status of event.
+    pub fn status(&self) -> Option<EventStatus> {
+        self.content.event_status
+    }
+
+    /// Returns `true` if event is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.status() == Some(EventStatus::Cancelled)
+    }
+
+    /// Returns number of participants, if reported.
+    pub fn participant_count(&self) -> Option<u64> {
+        self.content.participant_count
+    }
+
+    /// Returns maximum number of participants, if limited.
+    pub fn capacity(&self) -> Option<u64> {
+        self.content.maximum_attendee_capacity
+            .filter(|capacity| *capacity > 0)
+    }
+
+    /// Returns number of places left, if reported.
+    pub fn remaining_capacity(&self) -> Option<u64> {
+        self.content.remaining_attendee_capacity
+    }
+
+    /// Returns `true` if event takes place online.
+    pub fn is_online(&self) -> bool {
+        self.content.is_online.unwrap_or(false)
+    }
+}
+
+impl Content {
+    /// Returns typed [Event] view if this content is `Event`.
+    pub fn event(&self) -> Option<Event<'_>> {
+        match self.entity_type() {
+            EntityType::Event => Some(Event { content: self }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::content::Content;
+    use crate::entity::is_supported_content_type;
+    use crate::event::{EventStatus, JoinMode};
+    use crate::object::ObjectTrait;
+
+    #[test]
+    fn test_mobilizon_event() {
+        let content: Content = serde_json::from_str(r#"{
+          "type": "Event",
+          "id": "https://mobilizon.z.y.x/events/xxxxxxxx",
+          "name": "Cat meetup",
+          "attributedTo": "https://mobilizon.z.y.x/@cats",
+          "published": "2024-01-01T00:00:00Z",
+          "startTime": "2024-02-01T18:00:00Z",
+          "endTime": "2024-02-01T20:00:00Z",
+          "timezone": "Europe/Paris",
+          "joinMode": "restricted",
+          "ical:status": "CONFIRMED",
+          "participantCount": 3,
+          "maximumAttendeeCapacity": 0,
+          "isOnline": false,
+          "content": "<p>Bring boxes</p>",
+          "location": {
+            "type": "Place",
+            "name": "Town hall",
+            "address": {
+              "type": "PostalAddress",
+              "streetAddress": "1 Rue de Rivoli",
+              "addressLocality": "Paris",
+              "postalCode": "75001",
+              "addressCountry": "France"
+            },
+            "latitude": 48.86,
+            "longitude": 2.35
+          }
+        }"#).unwrap();
+
+        assert!(is_supported_content_type(content.entity_type()));
+
+        let event = content.event().unwrap();
+
+        assert_eq!(Some(Utc.with_ymd_and_hms(2024, 2, 1, 18, 0, 0).unwrap()), event.start_time());
+        assert_eq!(Some(Utc.with_ymd_and_hms(2024, 2, 1, 20, 0, 0).unwrap()), event.end_time());
+        assert_eq!(Some(JoinMode::Restricted), event.join_mode());
+        assert_eq!(Some(EventStatus::Confirmed), event.status());
+        assert_eq!(Some(3), event.participant_count());
+        assert_eq!(None, event.capacity());
+
+        let location = event.location().unwrap();
+
+        assert_eq!(Some((48.86, 2.35)), location.coordinates());
+
+        assert_eq!(
+            "1 Rue de Rivoli, 75001, Paris, France",
+            location.address.as_ref().unwrap().to_text()
+        );
+    }
+
+    #[test]
+    fn test_gancio_event() {
+        let content: Content = serde_json::from_str(r#"{
+          "type": "Event",
+          "id": "https://gancio.z.y.x/federation/m/42",
+          "name": "Concert",
+          "attributedTo": "https://gancio.z.y.x/federation/u/relay",
+          "published": "2024-01-01T00:00:00Z",
+          "startTime": "2024-03-01T21:00:00+01:00",
+          "location": {
+            "type": "Place",
+            "name": "Club",
+            "address": "Via Roma 1, Torino"
+          }
+        }"#).unwrap();
+
+        let event = content.event().unwrap();
+        let location = event.location().unwrap();
+
+        assert_eq!("Via Roma 1, Torino", location.address.as_ref().unwrap().to_text());
+        assert_eq!(None, location.coordinates());
+        assert!(!event.is_cancelled());
+    }
+}
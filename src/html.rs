@@ -0,0 +1,669 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::content::Content;
+
+/// Kind of semantic span in extracted text.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SpanKind {
+    /// Plain link.
+    Link,
+
+    /// Mention of actor, e.g. `@user@host`.
+    Mention,
+
+    /// Hashtag, e.g. `#tag`.
+    Hashtag,
+
+    /// Custom emoji, e.g. `:blobcat:`.
+    Emoji,
+}
+
+/// Semantic span in extracted text.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TextSpan {
+    /// What span represents.
+    pub kind: SpanKind,
+
+    /// Byte offset of span start in extracted text.
+    pub start: usize,
+
+    /// Byte offset of span end in extracted text, exclusive.
+    pub end: usize,
+
+    /// Link target, if span is a link.
+    pub href: Option<String>,
+}
+
+impl TextSpan {
+    /// Returns text covered by this span.
+    pub fn text<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.start..self.end]
+    }
+}
+
+/// Plain text extracted from HTML along with semantic spans.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ExtractedText {
+    /// Plain text.
+    pub text: String,
+
+    /// Spans ordered by start offset.
+    pub spans: Vec<TextSpan>,
+}
+
+impl ExtractedText {
+    /// Returns spans of given `kind`.
+    pub fn spans_of(&self, kind: SpanKind) -> impl Iterator<Item=&TextSpan> {
+        self.spans.iter()
+            .filter(move |span| span.kind == kind)
+    }
+}
+
+/// Elements that start a new paragraph.
+const PARAGRAPH_ELEMENTS: [&str; 10] = [
+    "p", "div", "blockquote", "pre", "h1", "h2", "h3", "h4", "h5", "h6",
+];
+
+/// Elements that start a new line.
+const LINE_ELEMENTS: [&str; 4] = ["li", "ul", "ol", "tr"];
+
+/// Elements which content is never shown.
+const HIDDEN_ELEMENTS: [&str; 3] = ["script", "style", "template"];
+
+/// Elements without closing tag.
+/// See: <https://html.spec.whatwg.org/multipage/syntax.html#void-elements>
+/// Obsolete `param` is still parsed as void by browsers.
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img",
+    "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// Custom emoji shortcode, e.g. `:blobcat:`.
+static EMOJI_SHORTCODE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r":[a-zA-Z0-9_+-]+:").unwrap()
+});
+
+/// Parsed start or end tag.
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    attributes: HashMap<String, String>,
+}
+
+impl Tag {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name)
+            .map(|value| value.as_str())
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.attribute("class")
+            .map(|classes| classes.split_whitespace().any(|x| x == class))
+            .unwrap_or(false)
+    }
+}
+
+/// Link being extracted.
+struct OpenLink {
+    start: usize,
+    href: Option<String>,
+    class_kind: Option<SpanKind>,
+}
+
+/// Stateful extractor that walks through HTML.
+struct Extractor {
+    text: String,
+    spans: Vec<TextSpan>,
+    /// Depth of open elements, used to find matching closing tags
+    /// of hidden and invisible elements.
+    depth: usize,
+    /// Depth at which hidden element was opened.
+    hidden_at: Option<usize>,
+    open_link: Option<OpenLink>,
+    /// Pending line breaks to be emitted before next text.
+    pending_breaks: usize,
+}
+
+impl Extractor {
+    fn new() -> Self {
+        Self {
+            text: String::new(),
+            spans: vec![],
+            depth: 0,
+            hidden_at: None,
+            open_link: None,
+            pending_breaks: 0,
+        }
+    }
+
+    /// Requests `count` line breaks before next text,
+    /// nothing is requested at the beginning of text.
+    fn request_breaks(&mut self, count: usize) {
+        if !self.text.is_empty() {
+            self.pending_breaks = self.pending_breaks.max(count);
+        }
+    }
+
+    fn push_text(&mut self, value: &str) {
+        if self.hidden_at.is_some() {
+            return;
+        }
+
+        for c in value.chars() {
+            // whitespace is collapsed as browsers do,
+            // non-breaking space is kept.
+            if c.is_whitespace() && c != '\u{a0}' {
+                if self.pending_breaks > 0 ||
+                    self.text.is_empty() ||
+                    self.text.ends_with([' ', '\n']) {
+                    continue;
+                }
+
+                self.text.push(' ');
+            } else {
+                self.flush_breaks();
+                self.text.push(c);
+            }
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag) {
+        let name = tag.name.as_str();
+        let is_void = tag.self_closing || VOID_ELEMENTS.contains(&name);
+
+        if self.hidden_at.is_some() {
+            if !is_void {
+                self.depth += 1;
+            }
+
+            return;
+        }
+
+        match name {
+            "br" => {
+                while self.text.ends_with(' ') {
+                    self.text.pop();
+                }
+
+                self.text.push('\n');
+                self.pending_breaks = 0;
+            }
+
+            "img" => {
+                // Pleroma and Friendica render custom emoji as images.
+                if let Some(alt) = tag.attribute("alt").filter(|alt| !alt.trim().is_empty()) {
+                    self.flush_breaks();
+
+                    let start = self.text.len();
+                    self.push_text(alt);
+
+                    if tag.has_class("emoji") || EMOJI_SHORTCODE.is_match(alt) {
+                        self.spans.push(TextSpan {
+                            kind: SpanKind::Emoji,
+                            start,
+                            end: self.text.len(),
+                            href: tag.attribute("src").map(|src| src.to_string()),
+                        });
+                    }
+                }
+            }
+
+            "a" => {
+                let is_tag_rel = tag.attribute("rel")
+                    .map(|rel| rel.split_whitespace().any(|x| x == "tag"))
+                    .unwrap_or(false);
+
+                let class_kind = if tag.has_class("hashtag") || is_tag_rel {
+                    Some(SpanKind::Hashtag)
+                } else if tag.has_class("mention") {
+                    Some(SpanKind::Mention)
+                } else {
+                    None
+                };
+
+                self.flush_breaks();
+
+                self.open_link = Some(OpenLink {
+                    start: self.text.len(),
+                    href: tag.attribute("href").map(|href| href.to_string()),
+                    class_kind,
+                });
+            }
+
+            _ => {
+                if PARAGRAPH_ELEMENTS.contains(&name) {
+                    self.request_breaks(2);
+                } else if LINE_ELEMENTS.contains(&name) {
+                    self.request_breaks(1);
+                }
+            }
+        }
+
+        if is_void {
+            return;
+        }
+
+        self.depth += 1;
+
+        // Mastodon hides parts of long URLs in `<span class="invisible">`.
+        if HIDDEN_ELEMENTS.contains(&name) || tag.has_class("invisible") {
+            self.hidden_at = Some(self.depth);
+        }
+    }
+
+    fn end_tag(&mut self, tag: Tag) {
+        let name = tag.name.as_str();
+
+        if VOID_ELEMENTS.contains(&name) {
+            return;
+        }
+
+        if let Some(hidden_at) = self.hidden_at {
+            if hidden_at == self.depth {
+                self.hidden_at = None;
+            }
+        }
+
+        self.depth = self.depth.saturating_sub(1);
+
+        if self.hidden_at.is_some() {
+            return;
+        }
+
+        if name == "a" {
+            self.close_link();
+        } else if PARAGRAPH_ELEMENTS.contains(&name) {
+            self.request_breaks(2);
+        } else if LINE_ELEMENTS.contains(&name) {
+            self.request_breaks(1);
+        }
+    }
+
+    /// Emits pending line breaks, trailing spaces are removed.
+    fn flush_breaks(&mut self) {
+        if self.pending_breaks == 0 {
+            return;
+        }
+
+        while self.text.ends_with(' ') {
+            self.text.pop();
+        }
+
+        for _ in 0..self.pending_breaks {
+            self.text.push('\n');
+        }
+
+        self.pending_breaks = 0;
+    }
+
+    /// Records span for currently open link, if any.
+    fn close_link(&mut self) {
+        let link = match self.open_link.take() {
+            None => return,
+            Some(link) => link,
+        };
+
+        let mut start = link.start;
+        let mut end = self.text.len();
+
+        while start < end && self.text[start..].starts_with(' ') {
+            start += 1;
+        }
+
+        while end > start && self.text[..end].ends_with(' ') {
+            end -= 1;
+        }
+
+        if start == end {
+            return;
+        }
+
+        let value = &self.text[start..end];
+
+        let kind = link.class_kind.unwrap_or_else(|| {
+            if value.starts_with('#') {
+                SpanKind::Hashtag
+            } else if value.starts_with('@') {
+                SpanKind::Mention
+            } else {
+                SpanKind::Link
+            }
+        });
+
+        self.spans.push(TextSpan {
+            kind,
+            start,
+            end,
+            href: link.href,
+        });
+    }
+
+    fn finish(mut self) -> ExtractedText {
+        self.close_link();
+
+        let trimmed_length = self.text.trim_end().len();
+        self.text.truncate(trimmed_length);
+
+        // custom emoji in text nodes, e.g. Mastodon keeps shortcodes as is.
+        let text = &self.text;
+
+        let emoji_spans: Vec<TextSpan> = EMOJI_SHORTCODE.find_iter(text)
+            // e.g. `12:30:45` is not emoji
+            .filter(|m| !text[..m.start()].ends_with(|c: char| c.is_alphanumeric()))
+            .filter(|m| !text[m.end()..].starts_with(|c: char| c.is_alphanumeric()))
+            .filter(|m| !self.spans.iter()
+                .any(|span| span.start <= m.start() && m.end() <= span.end)
+            )
+            .map(|m| TextSpan {
+                kind: SpanKind::Emoji,
+                start: m.start(),
+                end: m.end(),
+                href: None,
+            })
+            .collect();
+
+        self.spans.extend(emoji_spans);
+        self.spans.retain(|span| span.end <= self.text.len());
+        self.spans.sort_by_key(|span| (span.start, span.end));
+
+        ExtractedText {
+            text: self.text,
+            spans: self.spans,
+        }
+    }
+}
+
+/// Parses tag starting at the beginning of `input`, which must start
+/// with `<`. Returns tag and its length, or `None` if `input` does not
+/// start with a well-formed tag.
+fn parse_tag(input: &str) -> Option<(Tag, usize)> {
+    let end = find_tag_end(input)?;
+    let raw = &input[..=end];
+    let inner = raw[1..raw.len() - 1].trim();
+
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, inner),
+    };
+
+    let (self_closing, inner) = match inner.strip_suffix('/') {
+        Some(rest) => (true, rest.trim_end()),
+        None => (false, inner),
+    };
+
+    let name_length = inner.find(|c: char| c.is_whitespace())
+        .unwrap_or(inner.len());
+
+    let name = inner[..name_length].to_ascii_lowercase();
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+
+    Some((
+        Tag {
+            name,
+            closing,
+            self_closing,
+            attributes: parse_attributes(&inner[name_length..]),
+        },
+        end + 1,
+    ))
+}
+
+/// Finds offset of `>` closing tag, skipping quoted attribute values.
+fn find_tag_end(input: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+
+    for (position, c) in input.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(position),
+            (None, '<') => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Parses attributes of tag, names are lowercased and values are decoded.
+fn parse_attributes(input: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = input.trim_start();
+
+    while !rest.is_empty() {
+        let name_length = rest.find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+
+        let name = rest[..name_length].to_ascii_lowercase();
+        rest = rest[name_length..].trim_start();
+
+        let mut value = String::new();
+
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            let after_equals = after_equals.trim_start();
+
+            let (raw_value, remaining) = match after_equals.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after_equals[1..];
+                    let value_end = body.find(quote).unwrap_or(body.len());
+                    (&body[..value_end], &body[(value_end + 1).min(body.len())..])
+                }
+
+                _ => {
+                    let value_end = after_equals.find(char::is_whitespace)
+                        .unwrap_or(after_equals.len());
+                    (&after_equals[..value_end], &after_equals[value_end..])
+                }
+            };
+
+            value = decode_entities(raw_value);
+            rest = remaining.trim_start();
+        }
+
+        if !name.is_empty() {
+            attributes.insert(name, value);
+        } else if rest.len() == input.len() {
+            break;
+        }
+    }
+
+    attributes
+}
+
+/// Decodes HTML character references, only the most common named
+/// references are supported.
+pub fn decode_entities(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_string();
+    }
+
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(position) = rest.find('&') {
+        decoded.push_str(&rest[..position]);
+        rest = &rest[position..];
+
+        let reference_end = rest.find(';')
+            .filter(|end| *end <= 10);
+
+        let decoded_char = reference_end.and_then(|end| {
+            let reference = &rest[1..end];
+
+            match reference {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => reference.strip_prefix('#')
+                    .and_then(|number| match number.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                        None => number.parse::<u32>().ok(),
+                    })
+                    .and_then(char::from_u32),
+            }
+        });
+
+        match (decoded_char, reference_end) {
+            (Some(c), Some(end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Extracts plain text from `html` keeping offsets of links, mentions,
+/// hashtags and custom emoji.
+///
+/// `<br>` is converted to line break, paragraphs are separated with
+/// empty line. Content of Mastodon's `<span class="invisible">`, that is
+/// hidden parts of long URLs, is skipped, full URL is available as `href`
+/// of link span.
+pub fn extract_text(html: &str) -> ExtractedText {
+    let mut extractor = Extractor::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        match rest.find('<') {
+            None => {
+                extractor.push_text(&decode_entities(rest));
+                break;
+            }
+
+            Some(position) => {
+                extractor.push_text(&decode_entities(&rest[..position]));
+                rest = &rest[position..];
+
+                if let Some(comment) = rest.strip_prefix("<!--") {
+                    rest = comment.find("-->")
+                        .map(|end| &comment[end + 3..])
+                        .unwrap_or("");
+                    continue;
+                }
+
+                match parse_tag(rest) {
+                    Some((tag, length)) => {
+                        rest = &rest[length..];
+
+                        match tag.closing {
+                            true => extractor.end_tag(tag),
+                            false => extractor.start_tag(tag),
+                        }
+                    }
+
+                    None => {
+                        extractor.push_text("<");
+                        rest = &rest[1..];
+                    }
+                }
+            }
+        }
+    }
+
+    extractor.finish()
+}
+
+/// Converts `html` to plain text, see [extract_text()].
+/// Could be used as `cleaner` for [Content::get_content_map()].
+pub fn html_to_text(html: &str) -> String {
+    extract_text(html).text
+}
+
+impl Content {
+    /// Returns plain text of `content` or `name` with semantic spans.
+    pub fn extract_text(&self) -> Option<ExtractedText> {
+        self.content.as_ref()
+            .or(self.object_entity.name.as_ref())
+            .map(|html| extract_text(html))
+    }
+
+    /// Returns content map with values converted to plain text by
+    /// [html_to_text()], see [Content::get_content_map()].
+    pub fn get_plain_text_content_map(&self) -> Option<HashMap<String, String>> {
+        self.get_content_map(&html_to_text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::{decode_entities, extract_text, html_to_text, SpanKind};
+
+    #[test]
+    fn test_mastodon_html_is_extracted() {
+        let html = concat!(
+            r#"<p>Hello <span class="h-card" translate="no"><a href="https://x.y/@cat" class="u-url mention">@<span>cat</span></a></span> :blobcat:</p>"#,
+            r#"<p>See <a href="https://example.com/very/long/path" target="_blank" rel="nofollow noopener noreferrer"><span class="invisible">https://</span><span class="ellipsis">example.com/very</span><span class="invisible">/long/path</span></a><br />and <a href="https://x.y/tags/cats" class="mention hashtag" rel="tag">#<span>cats</span></a></p>"#,
+        );
+
+        let extracted = extract_text(html);
+
+        assert_eq!(
+            "Hello @cat :blobcat:\n\nSee example.com/very\nand #cats",
+            extracted.text
+        );
+
+        let kinds: Vec<SpanKind> = extracted.spans.iter()
+            .map(|span| span.kind)
+            .collect();
+
+        assert_eq!(
+            vec![SpanKind::Mention, SpanKind::Emoji, SpanKind::Link, SpanKind::Hashtag],
+            kinds
+        );
+
+        let link = extracted.spans_of(SpanKind::Link).next().unwrap();
+
+        assert_eq!("example.com/very", link.text(&extracted.text));
+        assert_eq!(Some("https://example.com/very/long/path"), link.href.as_deref());
+
+        let mention = extracted.spans_of(SpanKind::Mention).next().unwrap();
+
+        assert_eq!("@cat", mention.text(&extracted.text));
+    }
+
+    #[test]
+    fn test_emoji_images_and_entities() {
+        let extracted = extract_text(
+            r#"<p>Tom &amp; Jerry &lt;3 <img class="emoji" alt=":heart:" src="https://x.y/heart.png"/></p>"#
+        );
+
+        assert_eq!("Tom & Jerry <3 :heart:", extracted.text);
+
+        let emoji = extracted.spans_of(SpanKind::Emoji).next().unwrap();
+
+        assert_eq!(":heart:", emoji.text(&extracted.text));
+        assert_eq!(Some("https://x.y/heart.png"), emoji.href.as_deref());
+
+        assert_eq!("a\u{a0}b &unknown; é", decode_entities("a&nbsp;b &unknown; &#xe9;"));
+        assert_eq!("1 < 2", html_to_text("1 < 2"));
+    }
+
+    #[test]
+    fn test_void_elements_in_hidden_span() {
+        let extracted = extract_text(concat!(
+            r#"<p><a href="https://example.com/a/very/long/path"><span class="invisible">https://</span>"#,
+            r#"<span class="ellipsis">example.com/a/</span><span class="invisible">very<wbr>/long/path</span></a>"#,
+            r#" and more<wbr>text</p>"#,
+        ));
+
+        assert_eq!("example.com/a/ and moretext", extracted.text);
+    }
+}
@@ -0,0 +1,280 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer};
+
+/// Scheme of FEP-ef61 portable object IDs.
+pub const PORTABLE_SCHEME: &str = "ap";
+
+/// Path prefix of FEP-ef61 gateway URLs.
+pub const GATEWAY_PATH_PREFIX: &str = "/.well-known/apgateway/";
+
+/// Identifier of object, not necessarily HTTP URL.
+///
+/// FEP-ef61 portable IDs, e.g. `ap://did:key:z6Mk.../actor`, are not
+/// valid [url::Url] as DID is not valid authority, so these are modelled
+/// separately. The same portable object could be served by gateways,
+/// e.g. `https://z.y.x/.well-known/apgateway/did:key:z6Mk.../actor`,
+/// such URLs are recognised as portable IDs too.
+/// See: <https://codeberg.org/fediverse/fep/src/branch/main/fep/ef61/fep-ef61.md>
+#[derive(Debug, Clone)]
+pub enum ObjectId {
+    /// Regular HTTP(S) URL.
+    Http(url::Url),
+
+    /// Portable ID.
+    Portable {
+        /// DID of authority, e.g. `did:key:z6Mk...`.
+        did: String,
+
+        /// Path, query and fragment following DID, e.g. `/actor`.
+        path: String,
+    },
+
+    /// Bare DID reference, e.g. `did:key:z6Mk...`.
+    Did(String),
+
+    /// Anything else, e.g. `nostr:` or `at://` IDs of bridges.
+    Other(String),
+}
+
+impl ObjectId {
+    /// Parses `value` to ID, returns `None` for empty values.
+    ///
+    /// `ap://` IDs with DID authority are parsed to [ObjectId::Portable],
+    /// other `ap:` values are kept as [ObjectId::Other].
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        if value.is_empty() {
+            return None;
+        }
+
+        if value.starts_with("ap:") {
+            return Some(Self::from_portable_scheme(value));
+        }
+
+        if value.starts_with("did:") {
+            return Some(ObjectId::Did(value.to_string()));
+        }
+
+        match url::Url::parse(value) {
+            Ok(url) => Some(Self::from(&url)),
+            Err(_) => Some(ObjectId::Other(value.to_string())),
+        }
+    }
+
+    /// Parses value with `ap` scheme, never fails.
+    fn from_portable_scheme(value: &str) -> Self {
+        value.strip_prefix("ap://")
+            .and_then(Self::portable)
+            .unwrap_or_else(|| ObjectId::Other(value.to_string()))
+    }
+
+    /// Splits `value` following `ap://` or gateway prefix into DID and path,
+    /// percent-encoded colons are decoded in DID only.
+    fn portable(value: &str) -> Option<Self> {
+        let split_at = value.find(['/', '?', '#'])
+            .unwrap_or(value.len());
+
+        let (did, path) = value.split_at(split_at);
+
+        let did = did.replace("%3A", ":")
+            .replace("%3a", ":");
+
+        if !did.starts_with("did:") {
+            return None;
+        }
+
+        Some(ObjectId::Portable {
+            did,
+            path: path.to_string(),
+        })
+    }
+
+    /// Returns canonical form of ID, gateway URLs are converted
+    /// to `ap://` IDs.
+    pub fn canonical(&self) -> String {
+        match self {
+            ObjectId::Http(url) => url.to_string(),
+            ObjectId::Portable { did, path } => format!("{PORTABLE_SCHEME}://{did}{path}"),
+            ObjectId::Did(did) => did.clone(),
+            ObjectId::Other(value) => value.clone(),
+        }
+    }
+
+    /// Returns `true` if this ID is portable.
+    pub fn is_portable(&self) -> bool {
+        matches!(self, ObjectId::Portable { .. })
+    }
+
+    /// Returns DID this ID is controlled by, if any.
+    pub fn did(&self) -> Option<&str> {
+        match self {
+            ObjectId::Portable { did, .. } => Some(did),
+            ObjectId::Did(did) => Some(did),
+            _ => None,
+        }
+    }
+
+    /// Returns URL to fetch object from, portable IDs are
+    /// mapped to URLs of `gateway`, e.g. `https://z.y.x`.
+    pub fn to_gateway_url(&self, gateway: &url::Url) -> Option<url::Url> {
+        match self {
+            ObjectId::Http(url) => Some(url.clone()),
+
+            ObjectId::Portable { did, path } => url::Url::parse(&format!(
+                "{}{GATEWAY_PATH_PREFIX}{did}{path}",
+                gateway.origin().ascii_serialization(),
+            )).ok(),
+
+            _ => None,
+        }
+    }
+}
+
+impl From<&url::Url> for ObjectId {
+    fn from(url: &url::Url) -> Self {
+        match url.scheme() {
+            "http" | "https" => {}
+
+            PORTABLE_SCHEME => return Self::from_portable_scheme(url.as_str()),
+
+            _ => return ObjectId::Other(url.to_string()),
+        }
+
+        let portable = url[url::Position::BeforePath..]
+            .strip_prefix(GATEWAY_PATH_PREFIX)
+            .and_then(Self::portable);
+
+        portable.unwrap_or_else(|| ObjectId::Http(url.clone()))
+    }
+}
+
+impl PartialEq for ObjectId {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for ObjectId {}
+
+impl fmt::Display for ObjectId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.canonical())
+    }
+}
+
+/// Parses `value` to [url::Url] to be used as object ID.
+///
+/// Portable IDs, e.g. `ap://did:key:z6Mk.../actor`, are not valid URLs,
+/// so colons of DID are percent-encoded, e.g. `ap://did%3Akey%3Az6Mk.../actor`.
+/// Such URLs are serialized in encoded form, [ObjectId::from()] converts
+/// them back to canonical form.
+pub fn parse_id_url(value: &str) -> Result<url::Url, url::ParseError> {
+    let err = match url::Url::parse(value) {
+        Ok(url) => return Ok(url),
+        Err(err) => err,
+    };
+
+    match ObjectId::parse(value) {
+        Some(ObjectId::Portable { did, path }) => url::Url::parse(&format!(
+            "{PORTABLE_SCHEME}://{}{path}",
+            did.replace(':', "%3A"),
+        )).map_err(|_| err),
+
+        _ => Err(err),
+    }
+}
+
+/// Deserializes object ID, see [parse_id_url()].
+pub(crate) fn deserialize_id_url<'de, D>(deserializer: D) -> Result<url::Url, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse_id_url(&value).map_err(serde::de::Error::custom)
+}
+
+/// Deserializes optional object ID, see [parse_id_url()].
+pub(crate) fn deserialize_optional_id_url<'de, D>(deserializer: D) -> Result<Option<url::Url>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| parse_id_url(&value).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Returns `true` if `left` and `right` IDs are the same in canonical
+/// form, e.g. portable ID and its gateway URL. Trailing slash is ignored,
+/// as some servers add it to actor IDs.
+pub fn ids_match(left: &str, right: &str) -> bool {
+    match (ObjectId::parse(left), ObjectId::parse(right)) {
+        (Some(left), Some(right)) => left.canonical().trim_end_matches('/') ==
+            right.canonical().trim_end_matches('/'),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::id::{ids_match, parse_id_url, ObjectId};
+
+    const DID: &str = "did:key:z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2";
+
+    #[test]
+    fn test_portable_ids() {
+        let id = ObjectId::parse(&format!("ap://{DID}/actor?type=Person")).unwrap();
+
+        assert!(id.is_portable());
+        assert_eq!(Some(DID), id.did());
+
+        let gateway_url = id.to_gateway_url(&url::Url::parse("https://z.y.x/users/xyz").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            format!("https://z.y.x/.well-known/apgateway/{DID}/actor?type=Person"),
+            gateway_url.as_str()
+        );
+
+        assert_eq!(id, ObjectId::from(&gateway_url));
+
+        assert!(ids_match(
+            &format!("ap://{DID}/actor#main-key"),
+            &format!("https://a.b/.well-known/apgateway/{}/actor#main-key", DID.replace(':', "%3A")),
+        ));
+
+        assert!(!ids_match(&format!("ap://{DID}/actor"), &format!("ap://{DID}/object")));
+        assert!(ids_match("https://z.y.x/users/xyz/", "https://z.y.x/users/xyz"));
+
+        assert!(matches!(ObjectId::parse("at://did:plc:xyz/app.bsky.feed.post/1"), Some(ObjectId::Other(_))));
+        assert!(matches!(ObjectId::parse("nostr:nevent1xyz"), Some(ObjectId::Other(_))));
+        assert!(matches!(ObjectId::parse(DID), Some(ObjectId::Did(_))));
+    }
+
+    #[test]
+    fn test_portable_ids_are_parsed_to_urls() {
+        let url = parse_id_url(&format!("ap://{DID}/actor")).unwrap();
+
+        assert_eq!("ap", url.scheme());
+        assert_eq!(format!("ap://{DID}/actor"), ObjectId::from(&url).canonical());
+
+        assert!(parse_id_url("https://z.y.x/users/xyz").is_ok());
+        assert!(parse_id_url("ap://z.y.x:port/actor").is_err());
+    }
+
+    #[test]
+    fn test_non_portable_ap_ids_are_kept() {
+        for value in ["ap:x", "ap:/x", "ap://x.y/actor"] {
+            let url = url::Url::parse(value).unwrap();
+
+            assert_eq!(Some(ObjectId::Other(url.to_string())), Some(ObjectId::from(&url)));
+            assert_eq!(Some(ObjectId::Other(value.to_string())), ObjectId::parse(value));
+            assert!(ids_match(value, value));
+        }
+
+        let id = ObjectId::parse(&format!("ap://{}/a%3Ab", DID.replace(':', "%3A"))).unwrap();
+
+        assert_eq!(format!("ap://{DID}/a%3Ab"), id.canonical());
+    }
+}
@@ -1,31 +1,44 @@
 use std::cmp::Ordering;
+
 use serde::{Deserialize, Serialize};
 
+use crate::entity::UnknownProperties;
+
 /// This structure represents image data structure
 /// that is not a complete ActivityPub object, note the absence of
 /// context and entity type.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Image {
     /// Link to actual image data.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<url::Url>,
 
     /// Description of image or just name of it.
     #[serde(alias = "name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
 
-    #[serde(alias = "mediaType")]
+    #[serde(rename = "mediaType", alias = "media_type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub media_type: Option<String>,
 
     /// Does this image have explicit or otherwise sensitive content?
     /// It is not very reliable property as nothing (but moderators)
     /// stops folks from ignoring this property.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sensitive: Option<bool>,
 
     /// Width of image in pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub width: Option<u32>,
 
     /// Height of image in pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub height: Option<u32>,
+
+    /// Properties not modelled by this crate, e.g. `type`.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
 }
 
 impl Image {
@@ -39,6 +52,7 @@ impl Image {
             sensitive: None,
             width: None,
             height: None,
+            unknown_properties: UnknownProperties::default(),
         }
     }
 }
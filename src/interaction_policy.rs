@@ -0,0 +1,428 @@
+use serde::{Deserialize, Serialize};
+
+use crate::activity::Activity;
+use crate::actor::{ActorReference, CompoundActorReference};
+use crate::content::{deserialize_lenient, Content};
+use crate::entity::{EntityType, UnknownProperties};
+use crate::id::ids_match;
+use crate::object::ObjectTrait;
+
+/// Kind of interaction with content.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InteractionKind {
+    /// Liking content, i.e. favouriting it.
+    Like,
+
+    /// Replying to content.
+    Reply,
+
+    /// Announcing content, i.e. boosting it.
+    Announce,
+}
+
+impl InteractionKind {
+    /// Returns kind of interaction `activity` is, if any.
+    /// `Create` is a reply if its object is in reply to something.
+    pub fn from_activity(activity: &Activity) -> Option<Self> {
+        match activity.entity_type() {
+            EntityType::Like => Some(InteractionKind::Like),
+            EntityType::Announce => Some(InteractionKind::Announce),
+
+            EntityType::Create => activity.object.get("inReplyTo")
+                .filter(|value| !value.is_null())
+                .map(|_| InteractionKind::Reply),
+
+            _ => None,
+        }
+    }
+
+    /// Returns type of authorization object approving this kind of
+    /// interaction, e.g. `LikeAuthorization`.
+    pub fn authorization_type(&self) -> &'static str {
+        match self {
+            InteractionKind::Like => "LikeAuthorization",
+            InteractionKind::Reply => "ReplyAuthorization",
+            InteractionKind::Announce => "AnnounceAuthorization",
+        }
+    }
+}
+
+/// Outcome of evaluation of [InteractionPolicy].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InteractionPermission {
+    /// Interaction is allowed without approval.
+    Allowed,
+
+    /// Interaction is allowed once author of content approves it.
+    ApprovalRequired,
+
+    /// Interaction is not allowed.
+    Denied,
+}
+
+/// Who could perform one kind of interaction.
+///
+/// Both lists contain actors, collections, e.g. followers, or
+/// public stream to allow anyone.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct InteractionRule {
+    /// Actors who could interact without approval.
+    /// Malformed lists are ignored, as is the one below.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub always: Option<CompoundActorReference>,
+
+    /// Actors who could interact with approval of author.
+    #[serde(rename = "approvalRequired")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_required: Option<CompoundActorReference>,
+}
+
+impl InteractionRule {
+    /// Returns permission for `actor` who is member of `collections`,
+    /// e.g. followers of content author.
+    pub fn evaluate(&self, actor: &url::Url, collections: &[&url::Url]) -> InteractionPermission {
+        if Self::includes(&self.always, actor, collections) {
+            return InteractionPermission::Allowed;
+        }
+
+        if Self::includes(&self.approval_required, actor, collections) {
+            return InteractionPermission::ApprovalRequired;
+        }
+
+        InteractionPermission::Denied
+    }
+
+    fn includes(
+        reference: &Option<CompoundActorReference>,
+        actor: &url::Url,
+        collections: &[&url::Url],
+    ) -> bool {
+        let references: Vec<&ActorReference> = match reference {
+            None => vec![],
+            Some(CompoundActorReference::Reference(reference)) => vec![reference],
+            Some(CompoundActorReference::List(list)) => list.iter().collect(),
+        };
+
+        references.into_iter().any(|reference| {
+            let id = reference.id().as_str();
+
+            reference.is_public() ||
+                ids_match(id, actor.as_str()) ||
+                collections.iter().any(|collection| ids_match(id, collection.as_str()))
+        })
+    }
+}
+
+/// GoToSocial's interaction policy of content.
+/// See: <https://docs.gotosocial.org/en/latest/federation/interaction_policy/>
+///
+/// Example:
+/// ```json
+/// "interactionPolicy": {
+///   "canLike": {
+///     "always": ["https://www.w3.org/ns/activitystreams#Public"],
+///     "approvalRequired": []
+///   },
+///   "canReply": {
+///     "always": ["https://z.y.x/users/xyz/followers"],
+///     "approvalRequired": ["https://www.w3.org/ns/activitystreams#Public"]
+///   }
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct InteractionPolicy {
+    /// Who could like content.
+    /// Malformed rules are ignored, as are ones below.
+    #[serde(rename = "canLike")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_like: Option<InteractionRule>,
+
+    /// Who could reply to content.
+    #[serde(rename = "canReply")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_reply: Option<InteractionRule>,
+
+    /// Who could announce content.
+    #[serde(rename = "canAnnounce")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_announce: Option<InteractionRule>,
+
+    /// Properties not modelled by this crate, e.g. future interactions.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+impl InteractionPolicy {
+    /// Returns rule for `kind` of interaction.
+    pub fn rule(&self, kind: InteractionKind) -> Option<&InteractionRule> {
+        match kind {
+            InteractionKind::Like => self.can_like.as_ref(),
+            InteractionKind::Reply => self.can_reply.as_ref(),
+            InteractionKind::Announce => self.can_announce.as_ref(),
+        }
+    }
+
+    /// Returns permission for `actor` who is member of `collections`
+    /// to perform `kind` of interaction.
+    /// Missing rule means anyone could interact, as before policies.
+    pub fn evaluate(
+        &self,
+        kind: InteractionKind,
+        actor: &url::Url,
+        collections: &[&url::Url],
+    ) -> InteractionPermission {
+        self.rule(kind)
+            .map(|rule| rule.evaluate(actor, collections))
+            .unwrap_or(InteractionPermission::Allowed)
+    }
+}
+
+/// Proof that author of content approved interaction with it,
+/// e.g. `ReplyAuthorization`.
+///
+/// Example:
+/// ```json
+/// {
+///   "type": "LikeAuthorization",
+///   "id": "https://z.y.x/users/xyz/authorizations/1",
+///   "attributedTo": "https://z.y.x/users/xyz",
+///   "interactingObject": "https://a.b/users/cat/likes/1",
+///   "interactionTarget": "https://z.y.x/users/xyz/statuses/1"
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct InteractionAuthorization {
+    /// Type of authorization, e.g. `LikeAuthorization`.
+    #[serde(rename = "type")]
+    pub authorization_type: String,
+
+    /// Authorization ID.
+    pub id: url::Url,
+
+    /// Author of content who approved interaction.
+    #[serde(rename = "attributedTo")]
+    pub attributed_to: CompoundActorReference,
+
+    /// Approved interaction, e.g. `Like` or reply.
+    #[serde(rename = "interactingObject")]
+    pub interacting_object: url::Url,
+
+    /// Content interaction is approved for.
+    #[serde(rename = "interactionTarget")]
+    pub interaction_target: url::Url,
+}
+
+impl InteractionAuthorization {
+    /// Returns `true` if this authorization approves `kind` of
+    /// interaction `interacting_object` with `target` authored by `author`.
+    ///
+    /// Authorization must be attributed to author and come from
+    /// the same origin, otherwise anyone could approve anything.
+    pub fn authorizes(
+        &self,
+        kind: InteractionKind,
+        interacting_object: &url::Url,
+        target: &url::Url,
+        author: &url::Url,
+    ) -> bool {
+        self.authorization_type == kind.authorization_type() &&
+            &self.interacting_object == interacting_object &&
+            &self.interaction_target == target &&
+            self.attributed_to.id() == Some(author) &&
+            self.id.origin() == author.origin()
+    }
+}
+
+impl Content {
+    /// Returns permission for `actor` who is member of `collections`
+    /// to perform `kind` of interaction with this content.
+    ///
+    /// Author could always interact with own content, as could mentioned
+    /// actors reply to it. Content without policy allows anything.
+    pub fn interaction_permission(
+        &self,
+        kind: InteractionKind,
+        actor: &url::Url,
+        collections: &[&url::Url],
+    ) -> InteractionPermission {
+        if self.author_id() == Some(actor) {
+            return InteractionPermission::Allowed;
+        }
+
+        if kind == InteractionKind::Reply && self.mentions(actor) {
+            return InteractionPermission::Allowed;
+        }
+
+        self.interaction_policy.as_ref()
+            .map(|policy| policy.evaluate(kind, actor, collections))
+            .unwrap_or(InteractionPermission::Allowed)
+    }
+
+    /// Returns reference to approval of this content as reply.
+    pub fn reply_authorization_id(&self) -> Option<&url::Url> {
+        self.reply_authorization.as_ref()
+            .or(self.approved_by.as_ref())
+    }
+
+    fn mentions(&self, actor: &url::Url) -> bool {
+        self.tag.iter()
+            .flat_map(|reference| reference.as_vec())
+            .filter(|tag| tag.entity_type() == EntityType::Mention)
+            .any(|tag| tag.object_id() == Some(actor))
+    }
+}
+
+impl Activity {
+    /// Returns reference to approval of this `Like` or `Announce`.
+    pub fn authorization_id(&self) -> Option<&url::Url> {
+        let authorization = match self.entity_type() {
+            EntityType::Like => self.like_authorization.as_ref(),
+            EntityType::Announce => self.announce_authorization.as_ref(),
+            _ => None,
+        };
+
+        authorization.or(self.approved_by.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::Content;
+    use crate::interaction_policy::{
+        InteractionAuthorization,
+        InteractionKind,
+        InteractionPermission,
+    };
+
+    fn url(value: &str) -> url::Url {
+        url::Url::parse(value).unwrap()
+    }
+
+    #[test]
+    fn test_interaction_policy() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "content": "<p>Text</p>",
+          "tag": [
+            {
+              "type": "Mention",
+              "href": "https://a.b/users/dog",
+              "name": "@dog@a.b"
+            }
+          ],
+          "interactionPolicy": {
+            "canLike": {
+              "always": ["as:Public"],
+              "approvalRequired": []
+            },
+            "canReply": {
+              "always": ["https://z.y.x/users/xyz/followers"],
+              "approvalRequired": ["https://www.w3.org/ns/activitystreams#Public"]
+            },
+            "canAnnounce": {
+              "always": "https://z.y.x/users/xyz"
+            }
+          }
+        }"#).unwrap();
+
+        let cat = url("https://a.b/users/cat");
+        let followers = url("https://z.y.x/users/xyz/followers");
+
+        let permission = |kind, actor: &url::Url, collections: &[&url::Url]|
+            content.interaction_permission(kind, actor, collections);
+
+        assert_eq!(InteractionPermission::Allowed, permission(InteractionKind::Like, &cat, &[]));
+        assert_eq!(InteractionPermission::ApprovalRequired, permission(InteractionKind::Reply, &cat, &[]));
+        assert_eq!(InteractionPermission::Allowed, permission(InteractionKind::Reply, &cat, &[&followers]));
+        assert_eq!(InteractionPermission::Denied, permission(InteractionKind::Announce, &cat, &[]));
+
+        assert_eq!(
+            InteractionPermission::Allowed,
+            permission(InteractionKind::Reply, &url("https://a.b/users/dog"), &[])
+        );
+
+        assert_eq!(
+            InteractionPermission::Allowed,
+            permission(InteractionKind::Announce, &url("https://z.y.x/users/xyz"), &[])
+        );
+    }
+
+    #[test]
+    fn test_interaction_authorization() {
+        let authorization: InteractionAuthorization = serde_json::from_str(r#"{
+          "type": "ReplyAuthorization",
+          "id": "https://z.y.x/users/xyz/authorizations/1",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "interactingObject": "https://a.b/users/cat/statuses/2",
+          "interactionTarget": "https://z.y.x/users/xyz/statuses/1"
+        }"#).unwrap();
+
+        let reply = url("https://a.b/users/cat/statuses/2");
+        let target = url("https://z.y.x/users/xyz/statuses/1");
+
+        assert!(authorization.authorizes(
+            InteractionKind::Reply,
+            &reply,
+            &target,
+            &url("https://z.y.x/users/xyz"),
+        ));
+
+        assert!(!authorization.authorizes(
+            InteractionKind::Like,
+            &reply,
+            &target,
+            &url("https://z.y.x/users/xyz"),
+        ));
+
+        assert!(!authorization.authorizes(
+            InteractionKind::Reply,
+            &reply,
+            &target,
+            &url("https://a.b/users/cat"),
+        ));
+    }
+
+    #[test]
+    fn test_malformed_policy_is_ignored() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Note",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "content": "<p>Text</p>",
+          "approvedBy": "",
+          "interactionPolicy": {
+            "canLike": {"always": {"anyOf": 1}},
+            "canReply": {
+              "always": ["https://a.b/users/cat/"],
+              "approvalRequired": 1
+            },
+            "canAnnounce": "nobody"
+          }
+        }"#).unwrap();
+
+        assert!(content.reply_authorization_id().is_none());
+
+        let policy = content.interaction_policy.as_ref().unwrap();
+
+        assert!(policy.can_like.as_ref().unwrap().always.is_none());
+        assert!(policy.can_announce.is_none());
+
+        assert_eq!(
+            InteractionPermission::Allowed,
+            content.interaction_permission(InteractionKind::Reply, &url("https://a.b/users/cat"), &[])
+        );
+        assert_eq!(
+            InteractionPermission::Denied,
+            content.interaction_permission(InteractionKind::Reply, &url("https://a.b/users/dog"), &[])
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+/// Language tag for content of unknown language.
+/// See: <https://www.rfc-editor.org/rfc/rfc5646#section-4.1>
+pub const UNDETERMINED_LANGUAGE: &str = "und";
+
+/// Explains how language of localized value is known.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LanguageOrigin {
+    /// Language is declared by `contentMap` or similar property.
+    Declared,
+
+    /// Language is not declared explicitly, it is either guessed from
+    /// other properties or set to [UNDETERMINED_LANGUAGE].
+    Inferred,
+}
+
+/// Value in some language along with the way language is known.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LocalizedContent {
+    /// Value itself, e.g. cleaned content.
+    pub text: String,
+
+    /// How language of value is known.
+    pub origin: LanguageOrigin,
+}
+
+/// Deprecated language subtags still seen in the wild,
+/// mostly from Java based software.
+const DEPRECATED_LANGUAGES: [(&str, &str); 3] = [
+    ("in", "id"),
+    ("iw", "he"),
+    ("ji", "yi"),
+];
+
+/// Normalizes BCP-47 language tag to its canonical casing,
+/// e.g. `EN_us` becomes `en-US` and `zh-hant` becomes `zh-Hant`.
+///
+/// Returns `None` if `tag` is not well-formed language tag,
+/// e.g. `default` key some servers put into `contentMap`.
+pub fn normalize_language_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-");
+    let mut subtags = tag.split('-');
+
+    let primary = subtags.next()?.to_ascii_lowercase();
+
+    let primary_is_valid = matches!(primary.len(), 2..=3) ||
+        primary == "x";
+
+    if !primary_is_valid || !primary.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let primary = DEPRECATED_LANGUAGES.iter()
+        .find(|(deprecated, _)| *deprecated == primary)
+        .map(|(_, replacement)| replacement.to_string())
+        .unwrap_or(primary);
+
+    let mut normalized = vec![primary];
+
+    // everything after singleton, e.g. `x-private`, is kept lowercase.
+    let mut after_singleton = normalized[0] == "x";
+
+    for subtag in subtags {
+        let is_valid = !subtag.is_empty() &&
+            subtag.len() <= 8 &&
+            subtag.chars().all(|c| c.is_ascii_alphanumeric());
+
+        if !is_valid {
+            return None;
+        }
+
+        let is_alphabetic = subtag.chars().all(|c| c.is_ascii_alphabetic());
+        let is_numeric = subtag.chars().all(|c| c.is_ascii_digit());
+
+        let subtag = match subtag.len() {
+            _ if after_singleton => subtag.to_ascii_lowercase(),
+
+            1 => {
+                after_singleton = true;
+                subtag.to_ascii_lowercase()
+            }
+
+            // region
+            2 if is_alphabetic => subtag.to_ascii_uppercase(),
+            3 if is_numeric => subtag.to_string(),
+
+            // script
+            4 if is_alphabetic => {
+                let lowercase = subtag.to_ascii_lowercase();
+                lowercase[..1].to_ascii_uppercase() + &lowercase[1..]
+            }
+
+            _ => subtag.to_ascii_lowercase(),
+        };
+
+        normalized.push(subtag);
+    }
+
+    Some(normalized.join("-"))
+}
+
+/// Normalizes keys of language map, see [normalize_language_tag()].
+///
+/// Keys that are not language tags are mapped to [UNDETERMINED_LANGUAGE].
+/// If several keys are normalized to the same tag, value of the
+/// lexicographically first original key wins.
+pub fn normalize_language_map<'a, V>(
+    map: impl IntoIterator<Item = (&'a str, V)>
+) -> HashMap<String, V> {
+    let mut entries: Vec<(&str, V)> = map.into_iter().collect();
+    entries.sort_by_key(|(key, _)| *key);
+
+    let mut normalized = HashMap::new();
+
+    for (key, value) in entries {
+        let key = normalize_language_tag(key)
+            .unwrap_or_else(|| UNDETERMINED_LANGUAGE.to_string());
+
+        normalized.entry(key).or_insert(value);
+    }
+
+    normalized
+}
+
+/// Parses `Accept-Language` header value into language ranges ordered
+/// by preference. Ranges with zero or malformed weight are skipped.
+///
+/// Example: `fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5`
+pub fn parse_accept_language(accept_language: &str) -> Vec<String> {
+    let mut ranges: Vec<(String, f32)> = accept_language.split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let range = parts.next()?.trim();
+
+            let weight = match parts.find_map(|part| part.trim().strip_prefix("q=")) {
+                None => 1.0,
+                Some(weight) => weight.trim().parse::<f32>().ok()?,
+            };
+
+            if weight <= 0.0 {
+                return None;
+            }
+
+            let range = match range {
+                "*" => "*".to_string(),
+                _ => normalize_language_tag(range)?,
+            };
+
+            Some((range, weight))
+        })
+        .collect();
+
+    // sort is stable, so ranges of equal weight keep their order.
+    ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    ranges.into_iter()
+        .map(|(range, _)| range)
+        .collect()
+}
+
+/// Returns `true` if `range` language range matches `tag`.
+/// Both are expected to be normalized.
+fn range_matches(range: &str, tag: &str) -> bool {
+    tag.eq_ignore_ascii_case(range) ||
+        tag.get(..range.len())
+            .map(|prefix| prefix.eq_ignore_ascii_case(range) &&
+                tag[range.len()..].starts_with('-')
+            )
+            .unwrap_or(false)
+}
+
+/// Picks language from `available` that suits `accept_language` best.
+///
+/// For each range in order of preference an exact match is looked up
+/// first, then more specific tag, e.g. `en-GB` for `en`, and then
+/// range is truncated, e.g. `zh-Hant-TW` falls back to `zh-Hant` and `zh`.
+/// Wildcard `*` matches the first available language.
+///
+/// Returns `None` if nothing matches.
+pub fn best_language<'a>(
+    available: &[&'a str],
+    accept_language: &str,
+) -> Option<&'a str> {
+    for range in parse_accept_language(accept_language) {
+        if range == "*" {
+            return available.first().copied();
+        }
+
+        let mut range = range.as_str();
+
+        loop {
+            let found = available.iter()
+                .find(|tag| tag.eq_ignore_ascii_case(range))
+                .or_else(|| available.iter()
+                    .find(|tag| range_matches(range, tag))
+                );
+
+            if let Some(tag) = found {
+                return Some(tag);
+            }
+
+            match range.rfind('-') {
+                Some(position) => range = &range[..position],
+                None => break,
+            }
+        }
+    }
+
+    None
+}
+
+/// Picks value from language `map` that suits `accept_language` best,
+/// see [best_language()].
+///
+/// If nothing matches, value of [UNDETERMINED_LANGUAGE] is returned, then
+/// value of lexicographically first language, so result is deterministic.
+pub fn best_localized<'a, V>(
+    map: &'a HashMap<String, V>,
+    accept_language: &str,
+) -> Option<(&'a str, &'a V)> {
+    let mut languages: Vec<&str> = map.keys()
+        .map(|key| key.as_str())
+        .collect();
+
+    languages.sort();
+
+    let language = best_language(&languages, accept_language)
+        .or_else(|| languages.iter()
+            .find(|language| **language == UNDETERMINED_LANGUAGE)
+            .copied()
+        )
+        .or_else(|| languages.first().copied())?;
+
+    map.get_key_value(language)
+        .map(|(language, value)| (language.as_str(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::language::{best_language, best_localized, normalize_language_tag};
+
+    #[test]
+    fn test_language_tags_are_normalized() {
+        let normalized = |tag: &str| normalize_language_tag(tag);
+
+        assert_eq!(Some("en".to_string()), normalized("EN"));
+        assert_eq!(Some("en-US".to_string()), normalized("en_us"));
+        assert_eq!(Some("zh-Hant-TW".to_string()), normalized("ZH-HANT-tw"));
+        assert_eq!(Some("es-419".to_string()), normalized("es-419"));
+        assert_eq!(Some("und".to_string()), normalized("und"));
+        assert_eq!(Some("he".to_string()), normalized("iw"));
+        assert_eq!(Some("de-x-formal".to_string()), normalized("de-X-Formal"));
+        assert_eq!(None, normalized("default"));
+        assert_eq!(None, normalized(""));
+        assert_eq!(None, normalized("en--US"));
+    }
+
+    #[test]
+    fn test_best_language() {
+        let available = ["de", "en-GB", "zh-Hant"];
+
+        assert_eq!(Some("de"), best_language(&available, "fr-CH, de;q=0.9, en;q=0.8"));
+        assert_eq!(Some("en-GB"), best_language(&available, "EN"));
+        assert_eq!(Some("zh-Hant"), best_language(&available, "zh-Hant-TW"));
+        assert_eq!(Some("de"), best_language(&available, "fr, *;q=0.1"));
+        assert_eq!(None, best_language(&available, "fr, en;q=0"));
+
+        let map = HashMap::from([
+            ("und".to_string(), 1),
+            ("ja".to_string(), 2),
+        ]);
+
+        assert_eq!(Some(("ja", &2)), best_localized(&map, "ja-JP"));
+        assert_eq!(Some(("und", &1)), best_localized(&map, "fr"));
+    }
+}
@@ -1,11 +1,30 @@
 pub mod activity;
 pub mod actor;
 pub mod attachment;
+pub mod blurhash;
+pub mod bridge;
+pub mod collection;
 pub mod content;
+pub mod content_entities;
 pub mod context;
 pub mod discoverable;
+pub mod duration;
 pub mod entity;
+pub mod event;
+pub mod html;
+pub mod id;
 pub mod image;
+pub mod interaction_policy;
+pub mod language;
+pub mod nodeinfo;
 pub mod object;
+pub mod poll;
+pub mod reaction;
+pub mod source;
+pub mod summary;
 pub mod tag;
+pub mod thread;
+pub mod threadiverse;
+pub mod update;
+pub mod video;
 pub mod object_guesser;
\ No newline at end of file
@@ -0,0 +1,500 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::context::{Context, ContextItem};
+
+/// Relation used in `/.well-known/nodeinfo` links for NodeInfo 2.0 schema.
+pub const NODEINFO_2_0_SCHEMA: &str = "http://nodeinfo.diaspora.software/ns/schema/2.0";
+
+/// Relation used in `/.well-known/nodeinfo` links for NodeInfo 2.1 schema.
+pub const NODEINFO_2_1_SCHEMA: &str = "http://nodeinfo.diaspora.software/ns/schema/2.1";
+
+/// Single link in `/.well-known/nodeinfo` document.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NodeInfoLink {
+    /// Schema this link refers to, e.g. [NODEINFO_2_1_SCHEMA].
+    pub rel: String,
+
+    /// Location of NodeInfo document.
+    pub href: url::Url,
+}
+
+/// Document served at `/.well-known/nodeinfo`.
+/// See: <https://github.com/jhass/nodeinfo/blob/main/PROTOCOL.md>
+///
+/// Example:
+/// ```json
+/// {
+///   "links": [
+///     {
+///       "rel": "http://nodeinfo.diaspora.software/ns/schema/2.0",
+///       "href": "https://mastodon.social/nodeinfo/2.0"
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NodeInfoLinks {
+    /// Links to NodeInfo documents of different schema versions.
+    pub links: Vec<NodeInfoLink>,
+}
+
+impl NodeInfoLinks {
+    /// Returns URL of NodeInfo document of the most recent schema version
+    /// supported by this crate, that is 2.1 is preferred over 2.0.
+    pub fn preferred_url(&self) -> Option<&url::Url> {
+        [NODEINFO_2_1_SCHEMA, NODEINFO_2_0_SCHEMA]
+            .into_iter()
+            .find_map(|schema| self.links.iter()
+                .find(|link| link.rel.trim_end_matches('/') == schema)
+            )
+            .map(|link| &link.href)
+    }
+}
+
+/// Software details reported in NodeInfo document.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Software {
+    /// Canonical name of software, e.g. `mastodon`.
+    pub name: String,
+
+    /// Version of software, format is up to software.
+    pub version: Option<String>,
+
+    /// Source code repository, NodeInfo 2.1 only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<url::Url>,
+
+    /// Home page of software, NodeInfo 2.1 only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<url::Url>,
+}
+
+/// Third party services server could interact with.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct Services {
+    /// Services server could retrieve messages from.
+    #[serde(default)]
+    pub inbound: Vec<String>,
+
+    /// Services server could publish messages to.
+    #[serde(default)]
+    pub outbound: Vec<String>,
+}
+
+/// Statistics on users of server.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct UsageUsers {
+    /// Total number of registered users.
+    pub total: Option<u64>,
+
+    /// Users signed in at least once in the last 180 days.
+    #[serde(rename = "activeHalfyear")]
+    pub active_half_year: Option<u64>,
+
+    /// Users signed in at least once in the last 30 days.
+    #[serde(rename = "activeMonth")]
+    pub active_month: Option<u64>,
+}
+
+/// Usage statistics of server.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct Usage {
+    /// Statistics on users.
+    #[serde(default)]
+    pub users: UsageUsers,
+
+    /// Number of posts made by local users.
+    #[serde(rename = "localPosts")]
+    pub local_posts: Option<u64>,
+
+    /// Number of comments made by local users.
+    #[serde(rename = "localComments")]
+    pub local_comments: Option<u64>,
+}
+
+/// NodeInfo 2.0/2.1 document.
+/// See: <http://nodeinfo.diaspora.software/schema.html>
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct NodeInfo {
+    /// Schema version, `2.0` or `2.1`.
+    pub version: String,
+
+    /// Software running on server.
+    pub software: Software,
+
+    /// Protocols supported by server, e.g. `activitypub`.
+    #[serde(default)]
+    pub protocols: Vec<String>,
+
+    /// Third party services server could interact with.
+    #[serde(default)]
+    pub services: Services,
+
+    /// Whether server accepts registrations of new users.
+    #[serde(rename = "openRegistrations")]
+    pub open_registrations: Option<bool>,
+
+    /// Usage statistics.
+    #[serde(default)]
+    pub usage: Usage,
+
+    /// Free form metadata, content is specific to software.
+    #[serde(default)]
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+impl NodeInfo {
+    /// Returns [ServerSoftware] inferred from reported software name.
+    pub fn server_software(&self) -> ServerSoftware {
+        ServerSoftware::from_name(&self.software.name)
+    }
+
+    /// Returns `true` if server declares support of ActivityPub protocol.
+    pub fn supports_activitypub(&self) -> bool {
+        self.protocols.iter()
+            .any(|protocol| protocol.eq_ignore_ascii_case("activitypub"))
+    }
+}
+
+/// Fediverse server software this crate knows how to tell apart.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ServerSoftware {
+    /// Akkoma, Pleroma fork.
+    Akkoma,
+
+    /// BookWyrm, social reading.
+    BookWyrm,
+
+    /// Bridgy Fed, bridge to Bluesky and websites.
+    BridgyFed,
+
+    /// CherryPick, Misskey fork.
+    CherryPick,
+
+    /// Fedibird, Mastodon fork.
+    Fedibird,
+
+    /// Firefish, Misskey fork formerly known as Calckey.
+    Firefish,
+
+    /// Friendica.
+    Friendica,
+
+    /// Funkwhale, audio sharing.
+    Funkwhale,
+
+    /// Gancio, event calendar.
+    Gancio,
+
+    /// GoToSocial.
+    GoToSocial,
+
+    /// Hometown, Mastodon fork.
+    Hometown,
+
+    /// Hubzilla.
+    Hubzilla,
+
+    /// Iceshrimp, Firefish fork.
+    Iceshrimp,
+
+    /// Kbin, link aggregator.
+    Kbin,
+
+    /// Lemmy, link aggregator.
+    Lemmy,
+
+    /// Mastodon.
+    Mastodon,
+
+    /// Mbin, Kbin fork.
+    Mbin,
+
+    /// Misskey.
+    Misskey,
+
+    /// Mobilizon, events and groups.
+    Mobilizon,
+
+    /// Mostr, bridge to Nostr.
+    Mostr,
+
+    /// PeerTube, video hosting.
+    PeerTube,
+
+    /// PieFed, link aggregator.
+    PieFed,
+
+    /// Pixelfed, photo sharing.
+    Pixelfed,
+
+    /// Pleroma.
+    Pleroma,
+
+    /// Sharkey, Misskey fork.
+    Sharkey,
+
+    /// Threads by Meta.
+    Threads,
+
+    /// WordPress with ActivityPub plugin.
+    WordPress,
+
+    /// WriteFreely, blogging.
+    WriteFreely,
+
+    /// Software is not known to this crate or could not be inferred.
+    Unknown,
+}
+
+impl fmt::Display for ServerSoftware {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl ServerSoftware {
+    /// Converts software name as reported in NodeInfo to [ServerSoftware].
+    /// Names are compared case-insensitively.
+    pub fn from_name(name: &str) -> Self {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "akkoma" => Self::Akkoma,
+            "bookwyrm" => Self::BookWyrm,
+            "bridgy-fed" | "bridgy fed" => Self::BridgyFed,
+            "cherrypick" => Self::CherryPick,
+            "fedibird" => Self::Fedibird,
+            "firefish" | "calckey" => Self::Firefish,
+            "friendica" => Self::Friendica,
+            "funkwhale" => Self::Funkwhale,
+            "gancio" => Self::Gancio,
+            "gotosocial" => Self::GoToSocial,
+            "hometown" => Self::Hometown,
+            "hubzilla" => Self::Hubzilla,
+            "iceshrimp" | "iceshrimp.net" => Self::Iceshrimp,
+            "kbin" => Self::Kbin,
+            "lemmy" => Self::Lemmy,
+            "mastodon" | "glitch-soc" => Self::Mastodon,
+            "mbin" => Self::Mbin,
+            "misskey" => Self::Misskey,
+            "mobilizon" => Self::Mobilizon,
+            "mostr" => Self::Mostr,
+            "peertube" => Self::PeerTube,
+            "piefed" => Self::PieFed,
+            "pixelfed" => Self::Pixelfed,
+            "pleroma" => Self::Pleroma,
+            "sharkey" => Self::Sharkey,
+            "threads" => Self::Threads,
+            "wordpress" => Self::WordPress,
+            "writefreely" => Self::WriteFreely,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Returns `true` for Misskey and its forks.
+    pub fn is_misskey_like(&self) -> bool {
+        matches!(
+            self,
+            Self::Misskey |
+            Self::Sharkey |
+            Self::Firefish |
+            Self::Iceshrimp |
+            Self::CherryPick
+        )
+    }
+
+    /// Returns `true` for Mastodon and its forks.
+    pub fn is_mastodon_like(&self) -> bool {
+        matches!(
+            self,
+            Self::Mastodon |
+            Self::Fedibird |
+            Self::Hometown
+        )
+    }
+
+    /// Returns `true` for Pleroma and its forks.
+    pub fn is_pleroma_like(&self) -> bool {
+        matches!(self, Self::Pleroma | Self::Akkoma)
+    }
+
+    /// Returns `true` for link aggregators, a.k.a. threadiverse.
+    pub fn is_threadiverse(&self) -> bool {
+        matches!(
+            self,
+            Self::Lemmy |
+            Self::Kbin |
+            Self::Mbin |
+            Self::PieFed
+        )
+    }
+
+    /// Guesses software from `@context` of object.
+    ///
+    /// It relies on namespaces and extension properties specific software
+    /// declares in its context, so it is only a hint. Most specific
+    /// namespaces are checked first, as e.g. Misskey declares `toot`
+    /// namespace as well.
+    pub fn guess_from_context(context: &Context) -> Option<Self> {
+        const DEFINITIONS: [(&str, ServerSoftware); 9] = [
+            ("sharkey", ServerSoftware::Sharkey),
+            ("firefish", ServerSoftware::Firefish),
+            ("misskey", ServerSoftware::Misskey),
+            ("_misskey_content", ServerSoftware::Misskey),
+            ("gts", ServerSoftware::GoToSocial),
+            ("pt", ServerSoftware::PeerTube),
+            ("lemmy", ServerSoftware::Lemmy),
+            ("mz", ServerSoftware::Mobilizon),
+            ("fedibird", ServerSoftware::Fedibird),
+        ];
+
+        for (definition, software) in DEFINITIONS {
+            if context.has_definition(definition) {
+                return Some(software);
+            }
+        }
+
+        let items = match context {
+            Context::ContextItem(item) => vec![item],
+            Context::List(items) => items.iter().collect(),
+        };
+
+        // Pleroma and Akkoma reference LitePub schema hosted
+        // by instance itself, e.g. `https://host/schemas/litepub-0.1.jsonld`.
+        let references_litepub = items.iter()
+            .any(|item| match item {
+                ContextItem::Url(url) => url.path().ends_with("/litepub-0.1.jsonld"),
+                ContextItem::Mapping(_) => false,
+            });
+
+        if references_litepub {
+            return Some(Self::Pleroma);
+        }
+
+        if context.has_definition("toot") {
+            return Some(Self::Mastodon);
+        }
+
+        None
+    }
+
+    /// Guesses software from raw ActivityPub payload.
+    /// Besides `@context` cues, properties specific to software are checked,
+    /// e.g. `_misskey_content`.
+    pub fn guess_from_payload(payload: &serde_json::Value) -> Option<Self> {
+        if payload.get("_misskey_content").is_some() ||
+            payload.get("_misskey_quote").is_some() {
+            let from_context = payload.get("@context")
+                .and_then(|value| serde_json::from_value::<Context>(value.clone()).ok())
+                .and_then(|context| Self::guess_from_context(&context))
+                .filter(|software| software.is_misskey_like());
+
+            return Some(from_context.unwrap_or(Self::Misskey));
+        }
+
+        payload.get("@context")
+            .and_then(|value| serde_json::from_value::<Context>(value.clone()).ok())
+            .and_then(|context| Self::guess_from_context(&context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::context::Context;
+    use crate::nodeinfo::{NodeInfo, NodeInfoLinks, ServerSoftware};
+
+    #[test]
+    fn test_nodeinfo_deserialize_success() {
+        let serialized = r#"{
+          "version": "2.0",
+          "software": {
+            "name": "mastodon",
+            "version": "4.2.8"
+          },
+          "protocols": [
+            "activitypub"
+          ],
+          "services": {
+            "outbound": [],
+            "inbound": []
+          },
+          "usage": {
+            "users": {
+              "total": 1000,
+              "activeMonth": 100,
+              "activeHalfyear": 500
+            },
+            "localPosts": 123456
+          },
+          "openRegistrations": true,
+          "metadata": {
+            "nodeName": "Mastodon",
+            "nodeDescription": ""
+          }
+        }"#;
+
+        let nodeinfo: NodeInfo = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(ServerSoftware::Mastodon, nodeinfo.server_software());
+        assert!(nodeinfo.supports_activitypub());
+        assert_eq!(Some(500), nodeinfo.usage.users.active_half_year);
+        assert_eq!(Some(123456), nodeinfo.usage.local_posts);
+    }
+
+    #[test]
+    fn test_preferred_nodeinfo_link() {
+        let serialized = r#"{
+          "links": [
+            {
+              "rel": "http://nodeinfo.diaspora.software/ns/schema/2.0",
+              "href": "https://x.y/nodeinfo/2.0"
+            },
+            {
+              "rel": "http://nodeinfo.diaspora.software/ns/schema/2.1",
+              "href": "https://x.y/nodeinfo/2.1"
+            }
+          ]
+        }"#;
+
+        let links: NodeInfoLinks = serde_json::from_str(serialized).unwrap();
+
+        assert_eq!(
+            "https://x.y/nodeinfo/2.1",
+            links.preferred_url().unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn test_software_guessed_from_payload() {
+        let misskey = serde_json::json!({
+            "@context": [
+                "https://www.w3.org/ns/activitystreams",
+                {
+                    "toot": "http://joinmastodon.org/ns#",
+                    "misskey": "https://misskey-hub.net/ns#"
+                }
+            ],
+            "_misskey_content": "$[rainbow :arisa_fuo_1:] xyz"
+        });
+
+        assert_eq!(
+            Some(ServerSoftware::Misskey),
+            ServerSoftware::guess_from_payload(&misskey)
+        );
+
+        let context: Context = serde_json::from_str(r#"[
+            "https://www.w3.org/ns/activitystreams",
+            {
+                "toot": "http://joinmastodon.org/ns#",
+                "indexable": "toot:indexable"
+            }
+        ]"#).unwrap();
+
+        assert_eq!(
+            Some(ServerSoftware::Mastodon),
+            ServerSoftware::guess_from_context(&context)
+        );
+    }
+}
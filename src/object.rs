@@ -1,8 +1,23 @@
 use serde::{Deserialize, Serialize};
 
 use crate::actor::CompoundActorReference;
+use crate::content::deserialize_lenient;
 use crate::context::Context;
 use crate::entity::{Entity, EntityType};
+use crate::id::deserialize_id_url;
+
+/// Checks if `media_type` is one of media types ActivityPub objects
+/// are served with, that is `application/activity+json` or
+/// `application/ld+json` with ActivityStreams profile.
+pub fn is_activitystreams_media_type(media_type: &str) -> bool {
+    let normalized: String = media_type.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    normalized == "application/activity+json" ||
+        normalized == r#"application/ld+json;profile="https://www.w3.org/ns/activitystreams""#
+}
 
 /// One of foundational types in ActivityPub,
 /// represents any sort of links.
@@ -14,11 +29,152 @@ pub struct Link {
 
     /// URL itself.
     pub href: url::Url,
+
+    /// Relation of linked resource to object, e.g. `canonical`.
+    /// Malformed values are ignored, as are ones below.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rel: Option<LinkRel>,
+
+    /// Media type of linked resource, e.g. `text/html` or `video/mp4`.
+    #[serde(rename = "mediaType", alias = "media_type")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    /// Language of linked resource.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hreflang: Option<String>,
+
+    /// Width of linked media in pixels.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// Height of linked media in pixels, PeerTube uses it
+    /// to indicate resolution of video files.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// Size of linked resource in bytes.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    /// Frame rate of linked video.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f64>,
+
+    /// Nested tags, e.g. PeerTube lists files of streaming playlist here.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Vec<LinkTag>>,
+}
+
+impl Link {
+    /// Returns [Link] objects nested into `tag` of this link.
+    pub fn nested_links(&self) -> Vec<&Link> {
+        self.tag.iter()
+            .flatten()
+            .filter_map(|tag| match tag {
+                LinkTag::Link(link) => Some(link.as_ref()),
+                LinkTag::Other(_) => None,
+            })
+            .collect()
+    }
+
+    /// Returns `true` if link has `rel` relation, case is ignored.
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rel.as_ref()
+            .map(|value| value.contains(rel))
+            .unwrap_or(false)
+    }
+
+    /// Returns `true` if link is `http` or `https` URL,
+    /// as opposed to e.g. `nostr:` or `magnet:` URIs.
+    pub fn is_http(&self) -> bool {
+        is_http_url(&self.href)
+    }
+
+    /// Returns `true` if media type of link matches `media_type`,
+    /// parameters and case are ignored.
+    pub fn has_media_type(&self, media_type: &str) -> bool {
+        self.media_type.as_deref()
+            .and_then(|value| value.split(';').next())
+            .map(|essence| essence.trim().eq_ignore_ascii_case(media_type))
+            .unwrap_or(false)
+    }
+}
+
+/// Helper enumeration to deal with different types of `rel` property.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum LinkRel {
+    /// Single relation.
+    Single(String),
+
+    /// Multiple relations, e.g. `["metadata", "video/mp4"]`.
+    List(Vec<String>),
+}
+
+impl LinkRel {
+    /// Returns vector of relations.
+    pub fn as_vec(&self) -> Vec<&str> {
+        match self {
+            LinkRel::Single(rel) => vec![rel.as_str()],
+            LinkRel::List(rels) => rels.iter().map(|rel| rel.as_str()).collect(),
+        }
+    }
+
+    /// Returns `true` if `rel` is one of relations, case is ignored.
+    pub fn contains(&self, rel: &str) -> bool {
+        self.as_vec()
+            .into_iter()
+            .any(|value| value.eq_ignore_ascii_case(rel))
+    }
+}
+
+/// Returns `true` if `url` has `http` or `https` scheme.
+pub fn is_http_url(url: &url::Url) -> bool {
+    matches!(url.scheme(), "http" | "https")
+}
+
+/// Item of `tag` list nested into [Link].
+///
+/// Example:
+/// ```json
+/// "tag": [
+///   {
+///     "type": "Infohash",
+///     "name": "xxxxxxxx"
+///   },
+///   {
+///     "type": "Link",
+///     "mediaType": "video/mp4",
+///     "href": "https://peertube.stream/static/streaming-playlists/hls/yyy/zzz-720-fragmented.mp4",
+///     "height": 720,
+///     "size": 12345678,
+///     "fps": 30
+///   }
+/// ]
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum LinkTag {
+    /// Nested [Link].
+    Link(Box<Link>),
+
+    /// Anything else, e.g. PeerTube's `Infohash`.
+    Other(serde_json::Value),
 }
 
 /// This enumeration keeps all types of links under one umbrella.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum UrlReference {
     /// Property is string with URL.
     Url(url::Url),
@@ -56,6 +212,56 @@ impl UrlReference {
             .into_iter()
             .next()
     }
+
+    /// Returns [Link] objects within this reference, plain URLs are skipped.
+    pub fn links(&self) -> Vec<&Link> {
+        match self {
+            UrlReference::Link(link) => vec![link],
+            UrlReference::LinkList(links) => links.iter().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Returns the first link of `media_type`, parameters and case are ignored.
+    pub fn find_by_media_type(&self, media_type: &str) -> Option<&Link> {
+        self.links()
+            .into_iter()
+            .find(|link| link.has_media_type(media_type))
+    }
+
+    /// Returns the first link with `rel` relation.
+    pub fn find_by_rel(&self, rel: &str) -> Option<&Link> {
+        self.links()
+            .into_iter()
+            .find(|link| link.has_rel(rel))
+    }
+
+    /// Returns URL a person would open in browser.
+    ///
+    /// `text/html` links are preferred, then plain URLs and links without
+    /// media type. Only `http` and `https` URLs are considered, so
+    /// e.g. HLS playlists of PeerTube or `nostr:` links of bridges
+    /// are not returned.
+    pub fn human_url(&self) -> Option<&url::Url> {
+        let links = self.links();
+
+        let html_link = links.iter()
+            .filter(|link| link.is_http())
+            .find(|link| link.has_media_type("text/html"))
+            .map(|link| &link.href);
+
+        if html_link.is_some() {
+            return html_link;
+        }
+
+        match self {
+            UrlReference::Url(url) => Some(url),
+            UrlReference::UrlList(urls) => urls.iter().find(|url| is_http_url(url)),
+            _ => links.into_iter().find(|link| link.media_type.is_none() && link.is_http())
+                .map(|link| &link.href),
+        }
+        .filter(|url| is_http_url(url))
+    }
 }
 
 /// Another foundation ActivityPub type - Object.
@@ -67,6 +273,8 @@ pub struct Object {
     pub entity: Entity,
 
     /// Unique object identifier.
+    /// Portable IDs are kept percent-encoded, see [crate::id::parse_id_url()].
+    #[serde(deserialize_with = "deserialize_id_url")]
     pub id: url::Url,
 
     /// Name of object, if any.
@@ -90,28 +298,8 @@ pub struct Object {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<UrlReference>,
 
-    // Example of Peertube preview.
-    //
-    // It is tiled so could not be just parsed as image, needs actual static preview.
-    //     preview": [
-    //     {
-    //       "type": "Image",
-    //       "rel": [
-    //         "storyboard"
-    //       ],
-    //       "url": [
-    //         {
-    //           "mediaType": "image/jpeg",
-    //           "href": "https://peertube.stream/lazy-static/storyboards/xyz.jpg",
-    //           "width": 1920,
-    //           "height": 1080,
-    //           "tileWidth": 192,
-    //           "tileHeight": 108,
-    //           "tileDuration": "PT1S"
-    //         }
-    //       ]
-    //     }
-    //   ],
+    // PeerTube previews, e.g. tiled storyboards, are modelled
+    // by [crate::video::VideoPreview].
 
     /// To whom it is sent, in most cases it is
     /// `https://www.w3.org/ns/activitystreams#Public` - special public URL.
@@ -119,6 +307,12 @@ pub struct Object {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub to: Option<CompoundActorReference>,
 
+    /// Point of time object was last updated, e.g. edited.
+    /// Malformed timestamps, e.g. ones without offset, are ignored.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<chrono::DateTime<chrono::Utc>>,
+
     /// Recipients to receive copy of content.
     #[cfg(feature = "more_properties")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -145,6 +339,7 @@ impl Object {
             name: None,
             url: None,
             to: None,
+            updated: None,
         }
     }
 
@@ -157,13 +352,15 @@ impl Object {
             name: None,
             url: None,
             to: None,
+            updated: None,
         }
     }
 
-    /// Returns any URL specified in the `url` field of this object.
+    /// Returns URL specified in the `url` field of this object,
+    /// the one person would open is preferred, see [UrlReference::human_url()].
     pub fn object_url(&self) -> Option<&url::Url> {
         self.url.as_ref()
-            .and_then(|x| x.any_url())
+            .and_then(|x| x.human_url().or_else(|| x.any_url()))
     }
 
     /// Returns true if object addressee matches `pattern`.
@@ -228,3 +425,87 @@ impl ObjectReference {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::object::{Object, UrlReference};
+
+    #[test]
+    fn test_human_url_is_preferred() {
+        let reference: UrlReference = serde_json::from_str(r#"[
+          {
+            "type": "Link",
+            "mediaType": "application/x-mpegURL",
+            "href": "https://peertube.stream/static/streaming-playlists/hls/yyy/master.m3u8"
+          },
+          {
+            "type": "Link",
+            "rel": "canonical",
+            "href": "nostr:nevent1xyz"
+          },
+          {
+            "type": "Link",
+            "mediaType": "text/html; charset=utf-8",
+            "hreflang": "en",
+            "href": "https://peertube.stream/w/abc"
+          }
+        ]"#).unwrap();
+
+        assert_eq!("https://peertube.stream/w/abc", reference.human_url().unwrap().as_str());
+        assert_eq!("nostr:nevent1xyz", reference.find_by_rel("Canonical").unwrap().href.as_str());
+
+        let reference: UrlReference = serde_json::from_str(r#"{
+            "type": "Link",
+            "rel": ["canonical"],
+            "href": "nostr:nevent1xyz"
+        }"#).unwrap();
+
+        assert!(reference.human_url().is_none());
+        assert!(reference.any_url().is_some());
+    }
+
+    #[test]
+    fn test_malformed_link_properties_are_ignored() {
+        let reference: UrlReference = serde_json::from_str(r#"[
+          {
+            "type": "Link",
+            "mediaType": "video/mp4",
+            "href": "https://peertube.stream/static/web-videos/abc-1080.mp4",
+            "width": "1920",
+            "height": "1080",
+            "size": -1,
+            "fps": "30",
+            "tag": { "type": "Infohash", "name": "xxxxxxxx" }
+          }
+        ]"#).unwrap();
+
+        let link = reference.find_by_media_type("video/mp4").unwrap();
+
+        assert_eq!(None, link.width);
+        assert_eq!(None, link.height);
+        assert_eq!(None, link.size);
+        assert!(link.fps.is_none());
+        assert!(link.nested_links().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_updated_is_ignored() {
+        for updated in [r#""2024-01-01T00:00:00""#, r#""yesterday""#, "1704067200"] {
+            let object: Object = serde_json::from_str(&format!(r#"{{
+              "type": "Note",
+              "id": "https://z.y.x/notes/1",
+              "updated": {updated}
+            }}"#)).unwrap();
+
+            assert!(object.updated.is_none());
+        }
+
+        let object: Object = serde_json::from_str(r#"{
+          "type": "Note",
+          "id": "https://z.y.x/notes/1",
+          "updated": "2024-01-01T00:00:00+02:00"
+        }"#).unwrap();
+
+        assert_eq!("2023-12-31T22:00:00Z", object.updated.unwrap().to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    }
+}
@@ -2,82 +2,345 @@ use once_cell::sync::Lazy;
 use regex::{Regex, RegexBuilder};
 use url::Url;
 use crate::actor::ActorReadableId;
+use crate::nodeinfo::ServerSoftware;
 
-/// Helper function to build case insensitive regex from
-/// given [pattern].
-fn build_regex(pattern: &str) -> Regex {
-    RegexBuilder::new(pattern)
-        .case_insensitive(true)
-        .build()
-        .unwrap()
+/// Kind of object URL is assumed to refer to.
+/// It is finer-grained than [GuessedType].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ObjectKind {
+    /// Person-like actor, e.g. account.
+    Actor,
+
+    /// Group-like actor, e.g. Lemmy community or PeerTube channel.
+    Group,
+
+    /// Short post, e.g. toot.
+    Note,
+
+    /// Long form post, e.g. blog post.
+    Article,
+
+    /// Link aggregator post, e.g. Lemmy post.
+    Page,
+
+    /// Video, e.g. PeerTube video.
+    Video,
+
+    /// Comment to post, e.g. Lemmy comment.
+    Comment,
 }
 
-/// List of known actor URLs formats.
-/// `user` capture group is used by functions in this module
-/// to extract account name from URL.
+impl ObjectKind {
+    /// Converts this kind into less detailed [GuessedType].
+    pub fn guessed_type(&self) -> GuessedType {
+        match self {
+            ObjectKind::Actor |
+            ObjectKind::Group => GuessedType::ACTOR,
+
+            ObjectKind::Note |
+            ObjectKind::Article |
+            ObjectKind::Page |
+            ObjectKind::Video |
+            ObjectKind::Comment => GuessedType::CONTENT,
+        }
+    }
+}
+
+/// Known URL path format.
+/// `user` capture group, if present in pattern, is used by functions
+/// in this module to extract account name from URL.
 ///
 /// While there is no guarantee this "username" matches actual
 /// username, in practice it is quite often matches.
-static ACTOR_REGEXES: Lazy<Vec<Regex>> = Lazy::new(|| {
-    vec![
-        build_regex(r"^/(users|u)/(?P<user>[^/]+)$"),
-        build_regex(r"^/profile/(?P<user>[^/]+)$"),
-        build_regex(r"^/ap/users/(?P<user>\d+)$"),
-    ]
-});
-
-/// List of known content URLs formats.
-static CONTENT_REGEXES: Lazy<Vec<Regex>> = Lazy::new(|| {
-    vec![
-        build_regex(r"^/users/[^/]+/statuses/\d+$"),    // Mastodon-like
-        build_regex(r"^/notes/.+$"),                    // Misskey-like
-        build_regex(r"^/p/([^/]+)/\d+$"),               // Pixelfed-like
-        build_regex(r"^/post/\d+$"),                    // Lemmy-like
-        build_regex(r"^/(notice|objects)/[^/]+$"),      // Soapbox-like
-        build_regex(r"^/ap/users/\d+/post/\d+/?$"),     // threads.net scheme
-    ]
-});
+#[derive(Debug, Clone)]
+pub struct UrlPattern {
+    /// Regular expression to match URL path against.
+    regex: Regex,
 
-/// This function matches [url] against known and extracts account
-/// name from it if possible.
-pub fn extract_username_from_url(url: &Url) -> Option<String> {
-    let path = url.path();
+    /// Kind of object URL refers to if path matches.
+    kind: ObjectKind,
+
+    /// Software known to use this URL format, if it is specific to it.
+    software: Option<ServerSoftware>,
+
+    /// How much match could be trusted, from `0.0` to `1.0`.
+    confidence: f32,
+}
+
+impl UrlPattern {
+    /// Creates new pattern for URL paths matching case-insensitive
+    /// regular expression `pattern`. Matching paths are assumed to refer
+    /// objects of `kind`, `confidence` is clamped to `0.0..=1.0` range,
+    /// NaN is treated as `0.0`.
+    pub fn new(
+        pattern: &str,
+        kind: ObjectKind,
+        confidence: f32,
+    ) -> Result<Self, regex::Error> {
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()?;
+
+        Ok(Self {
+            regex,
+            kind,
+            software: None,
+            confidence: match confidence.is_nan() {
+                true => 0.0,
+                false => confidence.clamp(0.0, 1.0),
+            },
+        })
+    }
+
+    /// Marks this pattern as specific to `software`.
+    pub fn with_software(mut self, software: ServerSoftware) -> Self {
+        self.software = Some(software);
+        self
+    }
+
+    /// Returns kind of object matching URLs refer to.
+    pub fn kind(&self) -> ObjectKind {
+        self.kind
+    }
 
-    if path.is_empty() {
-        return None;
+    /// Returns software this pattern is specific to, if any.
+    pub fn software(&self) -> Option<ServerSoftware> {
+        self.software
     }
 
-    for regex in ACTOR_REGEXES.iter() {
-        if let Some(captures) = regex.captures(path) {
-            let group_value = captures.name("user");
+    /// Returns confidence of match.
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Matches `path` against this pattern.
+    fn match_path(&self, path: &str) -> Option<UrlMatch> {
+        self.regex.captures(path)
+            .map(|captures| UrlMatch {
+                kind: self.kind,
+                software: self.software,
+                confidence: self.confidence,
+                username: captures.name("user")
+                    .map(|value| value.as_str().to_string()),
+            })
+    }
+}
+
+/// Result of matching URL against [UrlPattern].
+#[derive(Debug, PartialEq, Clone)]
+pub struct UrlMatch {
+    /// Kind of object URL is assumed to refer to.
+    pub kind: ObjectKind,
+
+    /// Software URL format is specific to, if any.
+    pub software: Option<ServerSoftware>,
+
+    /// How much match could be trusted, from `0.0` to `1.0`.
+    pub confidence: f32,
 
-            if group_value.is_none() {
-                continue;
+    /// Account name extracted from URL, if pattern captures it.
+    pub username: Option<String>,
+}
+
+/// Registry of known URL formats.
+///
+/// [UrlPatternRegistry::with_known_patterns()] is populated with formats
+/// this crate knows about, callers could register more patterns at runtime
+/// with [UrlPatternRegistry::register()].
+#[derive(Debug, Clone)]
+pub struct UrlPatternRegistry {
+    patterns: Vec<UrlPattern>,
+}
+
+impl Default for UrlPatternRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UrlPatternRegistry {
+    /// Creates empty registry.
+    pub fn new() -> Self {
+        Self {
+            patterns: vec![],
+        }
+    }
+
+    /// Creates registry populated with URL formats this crate knows about.
+    pub fn with_known_patterns() -> Self {
+        let mut registry = Self::new();
+
+        for (pattern, kind, software, confidence) in KNOWN_PATTERNS {
+            let mut url_pattern = UrlPattern::new(pattern, kind, confidence)
+                .unwrap();
+
+            if let Some(software) = software {
+                url_pattern = url_pattern.with_software(software);
             }
 
-            return Some(group_value.unwrap().as_str().to_string());
+            registry.register(url_pattern);
         }
+
+        registry
+    }
+
+    /// Adds `pattern` to this registry.
+    pub fn register(&mut self, pattern: UrlPattern) {
+        self.patterns.push(pattern);
+    }
+
+    /// Returns patterns specific to `software`.
+    pub fn patterns_for(
+        &self,
+        software: ServerSoftware,
+    ) -> impl Iterator<Item=&UrlPattern> {
+        self.patterns.iter()
+            .filter(move |pattern| pattern.software == Some(software))
+    }
+
+    /// Returns all matches for [url] ordered by confidence, the most
+    /// confident match goes first. Matches of equal confidence keep
+    /// order of registration.
+    pub fn matches(&self, url: &Url) -> Vec<UrlMatch> {
+        let path = url.path();
+
+        if path.is_empty() {
+            return vec![];
+        }
+
+        let mut matches: Vec<UrlMatch> = self.patterns.iter()
+            .filter_map(|pattern| pattern.match_path(path))
+            .collect();
+
+        matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        matches
+    }
+
+    /// Returns the most confident match for [url] if any.
+    pub fn best_match(&self, url: &Url) -> Option<UrlMatch> {
+        self.matches(url)
+            .into_iter()
+            .next()
+    }
+
+    /// Returns account name extracted from [url] by the most confident
+    /// actor-like match, e.g. Person or Group, if any.
+    pub fn extract_username(&self, url: &Url) -> Option<String> {
+        self.matches(url)
+            .into_iter()
+            .filter(|m| m.kind.guessed_type() == GuessedType::ACTOR)
+            .find_map(|m| m.username)
+    }
+
+    /// Returns [ActorReadableId] made of account name extracted from [url]
+    /// and its host name, see [UrlPatternRegistry::extract_username()].
+    pub fn extract_actor_readable_id(&self, url: &Url) -> Option<ActorReadableId> {
+        let username = self.extract_username(url)?;
+        let host = url.host()?;
+
+        Some(ActorReadableId {
+            server: host.to_string(),
+            username,
+        })
+    }
+
+    /// Returns [GuessedType] of the most confident match for [url].
+    pub fn guess_object_type(&self, url: &Url) -> GuessedType {
+        self.best_match(url)
+            .map(|m| m.kind.guessed_type())
+            .unwrap_or(GuessedType::UNKNOWN)
     }
 
-    None
+    /// Returns the most confident match for [url] when server software
+    /// is known. Patterns specific to `software` take priority, then
+    /// generic patterns are checked. Patterns of other software are used
+    /// only if nothing else matches.
+    pub fn best_match_for_software(
+        &self,
+        url: &Url,
+        software: ServerSoftware,
+    ) -> Option<UrlMatch> {
+        let matches = self.matches(url);
+
+        let specific = matches.iter()
+            .find(|m| m.software == Some(software));
+
+        let generic = matches.iter()
+            .find(|m| m.software.is_none());
+
+        specific.or(generic)
+            .or(matches.first())
+            .cloned()
+    }
+}
+
+/// Known URL formats, each entry is `(pattern, kind, software, confidence)`.
+/// Patterns are matched against URL path only.
+const KNOWN_PATTERNS: [(&str, ObjectKind, Option<ServerSoftware>, f32); 27] = [
+    // Actors
+    (r"^/(users|u)/(?P<user>[^/]+)$", ObjectKind::Actor, None, 0.8),
+    (r"^/@(?P<user>[^/@]+)$", ObjectKind::Actor, None, 0.7),
+    (r"^/profile/(?P<user>[^/]+)$", ObjectKind::Actor, Some(ServerSoftware::Friendica), 0.7),
+    (r"^/ap/users/(?P<user>\d+)/?$", ObjectKind::Actor, Some(ServerSoftware::Threads), 0.9),
+    (r"^/accounts/(?P<user>[^/]+)$", ObjectKind::Actor, Some(ServerSoftware::PeerTube), 0.9),
+    (r"^/a/(?P<user>[^/]+)/?$", ObjectKind::Actor, Some(ServerSoftware::PeerTube), 0.7),
+    (r"^/author/(?P<user>[^/]+)/?$", ObjectKind::Actor, Some(ServerSoftware::WordPress), 0.7),
+
+    // Groups
+    (r"^/video-channels/(?P<user>[^/]+)$", ObjectKind::Group, Some(ServerSoftware::PeerTube), 0.9),
+    (r"^/c/(?P<user>[^/]+)/?$", ObjectKind::Group, Some(ServerSoftware::PeerTube), 0.5),
+    (r"^/c/(?P<user>[^/]+)/?$", ObjectKind::Group, Some(ServerSoftware::Lemmy), 0.6),
+    (r"^/m/(?P<user>[^/]+)$", ObjectKind::Group, Some(ServerSoftware::Mbin), 0.6),
+
+    // Content
+    (r"^/users/[^/]+/statuses/\d+$", ObjectKind::Note, Some(ServerSoftware::Mastodon), 0.9),
+    (r"^/@[^/]+/\d+$", ObjectKind::Note, Some(ServerSoftware::Mastodon), 0.8),
+    (r"^/users/[^/]+/statuses/[0-9a-hjkmnp-tv-z]{26}$", ObjectKind::Note, Some(ServerSoftware::GoToSocial), 0.95),
+    (r"^/@[^/]+/statuses/[0-9a-hjkmnp-tv-z]{26}$", ObjectKind::Note, Some(ServerSoftware::GoToSocial), 0.95),
+    (r"^/notes/.+$", ObjectKind::Note, Some(ServerSoftware::Misskey), 0.8),
+    (r"^/p/([^/]+)/\d+$", ObjectKind::Note, Some(ServerSoftware::Pixelfed), 0.8),
+    (r"^/post/\d+$", ObjectKind::Page, Some(ServerSoftware::Lemmy), 0.8),
+    (r"^/comment/\d+$", ObjectKind::Comment, Some(ServerSoftware::Lemmy), 0.9),
+    (r"^/m/[^/]+/t/\d+(/[^/]*)?$", ObjectKind::Page, Some(ServerSoftware::Mbin), 0.8),
+    (r"^/objects/[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$", ObjectKind::Note, Some(ServerSoftware::Akkoma), 0.85),
+    (r"^/(notice|objects)/[^/]+$", ObjectKind::Note, None, 0.6),
+    (r"^/display/[^/]+$", ObjectKind::Note, Some(ServerSoftware::Friendica), 0.8),
+    (r"^/ap/users/\d+/post/\d+/?$", ObjectKind::Note, Some(ServerSoftware::Threads), 0.9),
+    (r"^/videos/watch/[^/]+$", ObjectKind::Video, Some(ServerSoftware::PeerTube), 0.9),
+    (r"^/w/[^/]+$", ObjectKind::Video, Some(ServerSoftware::PeerTube), 0.8),
+    (r"^/\d{4}/\d{2}/(\d{2}/)?[^/]+/?$", ObjectKind::Article, Some(ServerSoftware::WordPress), 0.6),
+];
+
+/// Registry of URL formats this crate knows about.
+static KNOWN_URL_PATTERNS: Lazy<UrlPatternRegistry> = Lazy::new(
+    UrlPatternRegistry::with_known_patterns
+);
+
+/// Returns registry of URL formats this crate knows about, which
+/// functions below use. To match against custom formats, extend
+/// a clone of it or [UrlPatternRegistry::with_known_patterns()] with
+/// [UrlPatternRegistry::register()] and use its methods instead.
+pub fn known_url_patterns() -> &'static UrlPatternRegistry {
+    &KNOWN_URL_PATTERNS
+}
+
+/// Returns the most confident match of [url] against URL formats
+/// this crate knows about, see [known_url_patterns()].
+pub fn guess_object_kind_from_url(url: &Url) -> Option<UrlMatch> {
+    KNOWN_URL_PATTERNS.best_match(url)
+}
+
+/// This function matches [url] against known and extracts account
+/// name from it if possible, see [UrlPatternRegistry::extract_username()].
+pub fn extract_username_from_url(url: &Url) -> Option<String> {
+    KNOWN_URL_PATTERNS.extract_username(url)
 }
 
 /// This function is similar to [extract_username_from_url()] but returns
 /// [ActorReadableId] instead of just string. [ActorReadableId] includes
 /// host name extracted from given [url] as well.
 pub fn extract_actor_readable_id_from_url(url: &Url) -> Option<ActorReadableId> {
-    if let Some(username) = extract_username_from_url(url) {
-        if let Some(host) = url.host() {
-            return Some(
-                ActorReadableId {
-                    server: host.to_string(),
-                    username
-                }
-            );
-        }
-    }
-
-    None
+    KNOWN_URL_PATTERNS.extract_actor_readable_id(url)
 }
 
 
@@ -99,26 +362,151 @@ pub enum GuessedType {
 ///  It is unreliable but if there is only object URL and not object definition itself or
 ///  if object is converted to tombstone this could serve as a last chance option.
 ///  Returns inferred [GuessedType].
+///
+///  Type of the most confident match is returned. Note that earlier versions
+///  checked actor formats before content ones, so URL matching both used to be
+///  guessed as actor regardless of confidence.
 pub fn guess_object_type_from_url(url: &Url) -> GuessedType {
-    let path = url.path();
+    KNOWN_URL_PATTERNS.guess_object_type(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use crate::nodeinfo::ServerSoftware;
+    use crate::object_guesser::{
+        extract_username_from_url,
+        guess_object_kind_from_url,
+        guess_object_type_from_url,
+        GuessedType,
+        known_url_patterns,
+        ObjectKind,
+        UrlPattern,
+        UrlPatternRegistry,
+    };
 
-    if path.is_empty() {
-        return GuessedType::UNKNOWN;
+    fn url(value: &str) -> Url {
+        Url::parse(value).unwrap()
     }
 
-    for regex in ACTOR_REGEXES.iter() {
-        if regex.is_match(path) {
-            return GuessedType::ACTOR;
-        }
+    #[test]
+    fn test_known_urls_are_guessed() {
+        let gts = guess_object_kind_from_url(
+            &url("https://gts.x.y/@user/statuses/01HQZ8P3W8M7V2X3Y4Z5A6B7C8")
+        ).unwrap();
+
+        assert_eq!(ObjectKind::Note, gts.kind);
+        assert_eq!(Some(ServerSoftware::GoToSocial), gts.software);
+
+        let video = guess_object_kind_from_url(
+            &url("https://peertube.stream/w/abcdefghijk")
+        ).unwrap();
+
+        assert_eq!(ObjectKind::Video, video.kind);
+
+        let comment = guess_object_kind_from_url(
+            &url("https://lemmy.world/comment/123")
+        ).unwrap();
+
+        assert_eq!(ObjectKind::Comment, comment.kind);
+
+        assert_eq!(
+            GuessedType::CONTENT,
+            guess_object_type_from_url(&url("https://z.y.x/users/xyz/statuses/123"))
+        );
+
+        assert_eq!(
+            GuessedType::UNKNOWN,
+            guess_object_type_from_url(&url("https://z.y.x/"))
+        );
+
+        assert_eq!(
+            Some("xyz".to_string()),
+            extract_username_from_url(&url("https://z.y.x/users/xyz"))
+        );
     }
 
-    for regex in CONTENT_REGEXES.iter() {
-        if regex.is_match(path) {
-            return GuessedType::CONTENT;
-        }
+    #[test]
+    fn test_software_hint_resolves_ambiguity() {
+        let registry = UrlPatternRegistry::with_known_patterns();
+        let community = url("https://lemmy.world/c/rust");
+
+        // Lemmy communities are more common than PeerTube channels.
+        let best = registry.best_match(&community).unwrap();
+
+        assert_eq!(Some(ServerSoftware::Lemmy), best.software);
+
+        let peertube = registry.best_match_for_software(
+            &community,
+            ServerSoftware::PeerTube,
+        ).unwrap();
+
+        assert_eq!(Some(ServerSoftware::PeerTube), peertube.software);
+
+        let lemmy = registry.best_match_for_software(
+            &community,
+            ServerSoftware::Lemmy,
+        ).unwrap();
+
+        assert_eq!(ObjectKind::Group, lemmy.kind);
+        assert_eq!(Some(ServerSoftware::Lemmy), lemmy.software);
+        assert_eq!(Some("rust".to_string()), lemmy.username);
     }
 
-    GuessedType::UNKNOWN
-}
+    #[test]
+    fn test_matches_are_ordered_by_confidence() {
+        let mut registry = known_url_patterns().clone();
+        let ambiguous = url("https://x.y/cats/abc");
+
+        registry.register(
+            UrlPattern::new(r"^/cats/(?P<user>[^/]+)$", ObjectKind::Actor, 0.5).unwrap()
+        );
 
+        registry.register(
+            UrlPattern::new(r"^/cats/[^/]+$", ObjectKind::Note, 0.9).unwrap()
+        );
 
+        let kinds: Vec<ObjectKind> = registry.matches(&ambiguous)
+            .into_iter()
+            .map(|m| m.kind)
+            .collect();
+
+        assert_eq!(vec![ObjectKind::Note, ObjectKind::Actor], kinds);
+
+        // content wins, though actor used to be checked first.
+        assert_eq!(GuessedType::CONTENT, registry.guess_object_type(&ambiguous));
+        assert_eq!(Some("abc".to_string()), registry.extract_username(&ambiguous));
+        assert_eq!("x.y", registry.extract_actor_readable_id(&ambiguous).unwrap().server);
+
+        assert_eq!(GuessedType::UNKNOWN, guess_object_type_from_url(&ambiguous));
+    }
+
+    #[test]
+    fn test_custom_pattern_is_registered() {
+        let mut registry = UrlPatternRegistry::default();
+        let custom = url("https://x.y/channel/abc");
+
+        assert!(registry.best_match(&custom).is_none());
+
+        registry.register(
+            UrlPattern::new(r"^/channel/(?P<user>[^/]+)$", ObjectKind::Group, 2.0)
+                .unwrap()
+        );
+
+        let custom_match = registry.best_match(&custom).unwrap();
+
+        assert_eq!(ObjectKind::Group, custom_match.kind);
+        assert_eq!(1.0, custom_match.confidence);
+        assert_eq!(None, custom_match.software);
+
+        registry.register(
+            UrlPattern::new(r"^/channel/[^/]+$", ObjectKind::Note, f32::NAN).unwrap()
+        );
+
+        let matches = registry.matches(&custom);
+
+        assert_eq!(ObjectKind::Group, matches[0].kind);
+        assert_eq!(0.0, matches[1].confidence);
+    }
+}
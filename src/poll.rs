@@ -0,0 +1,346 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::activity::Activity;
+use crate::content::{deserialize_lenient, Content};
+use crate::entity::UnknownProperties;
+use crate::object::ObjectTrait;
+use crate::update::{ContentDiff, UpdateError};
+
+/// Replies collection of poll option, only number of votes is interesting.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PollOptionReplies {
+    /// Number of votes for option.
+    #[serde(rename = "totalItems")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_items: Option<u64>,
+
+    /// Properties not modelled by this crate, e.g. `type`.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+/// Poll option as listed in `oneOf` or `anyOf` properties of `Question`.
+///
+/// Example:
+/// ```json
+/// {
+///   "type": "Note",
+///   "name": "Yes",
+///   "replies": {
+///     "type": "Collection",
+///     "totalItems": 3
+///   }
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PollOption {
+    /// Option text.
+    pub name: String,
+
+    /// Votes for option.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replies: Option<PollOptionReplies>,
+
+    /// Properties not modelled by this crate, e.g. `type`.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+impl PollOption {
+    /// Returns number of votes for this option, if reported.
+    pub fn votes(&self) -> Option<u64> {
+        self.replies.as_ref()
+            .and_then(|replies| replies.total_items)
+    }
+}
+
+/// Helper enumeration to deal with different types of `closed` property.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum PollClosed {
+    /// Point of time poll was closed, Mastodon's way.
+    At(DateTime<Utc>),
+
+    /// Just a flag.
+    Flag(bool),
+}
+
+/// Poll view over `Question` content.
+#[derive(Debug, Clone)]
+pub struct Poll<'a> {
+    /// Poll options in order of declaration.
+    pub options: &'a [PollOption],
+
+    /// `true` if multiple options could be chosen.
+    pub multiple_choice: bool,
+
+    /// Point of time poll ends.
+    pub end_time: Option<DateTime<Utc>>,
+
+    /// Indicates poll is closed.
+    pub closed: Option<&'a PollClosed>,
+
+    /// Number of people voted in poll, if reported.
+    pub voters_count: Option<u64>,
+}
+
+impl Poll<'_> {
+    /// Returns `true` if poll is explicitly closed or its end time
+    /// is not later than `now`.
+    pub fn is_closed(&self, now: DateTime<Utc>) -> bool {
+        match self.closed {
+            Some(PollClosed::At(closed_at)) if *closed_at <= now => return true,
+            Some(PollClosed::Flag(true)) => return true,
+            _ => {}
+        }
+
+        self.end_time
+            .map(|end_time| end_time <= now)
+            .unwrap_or(false)
+    }
+
+    /// Returns sum of votes for all options.
+    pub fn total_votes(&self) -> u64 {
+        self.options.iter()
+            .filter_map(|option| option.votes())
+            .sum()
+    }
+
+    /// Returns number of people voted in poll.
+    /// If it is not reported then for single choice poll it equals to
+    /// total number of votes, for multiple choice poll it is unknown.
+    pub fn voters(&self) -> Option<u64> {
+        if self.voters_count.is_some() {
+            return self.voters_count;
+        }
+
+        match self.multiple_choice {
+            true => None,
+            false => Some(self.total_votes()),
+        }
+    }
+}
+
+impl Content {
+    /// Returns poll view if this content has poll options.
+    ///
+    /// Empty option lists are ignored, as Pleroma sends empty `oneOf`
+    /// along with options of multiple choice poll in `anyOf`.
+    pub fn poll(&self) -> Option<Poll<'_>> {
+        let one_of = self.one_of.as_ref().filter(|options| !options.is_empty());
+        let any_of = self.any_of.as_ref().filter(|options| !options.is_empty());
+
+        let (options, multiple_choice) = match (one_of, any_of) {
+            (Some(options), _) => (options, false),
+            (None, Some(options)) => (options, true),
+            (None, None) => return None,
+        };
+
+        Some(Poll {
+            options,
+            multiple_choice,
+            end_time: self.end_time,
+            closed: self.closed.as_ref(),
+            voters_count: self.voters_count,
+        })
+    }
+
+    /// Applies poll results from `update` to this content.
+    ///
+    /// Servers send `Update` activities as votes are counted, those usually
+    /// do not have new `updated` timestamp, so unlike [Content::apply_update()]
+    /// only poll related properties are taken from `update`. Update is
+    /// rejected if options are changed, as it is an edit, or if it has
+    /// fewer votes than stored poll. End time and closing time are kept
+    /// if `update` omits them.
+    pub fn apply_poll_results(&mut self, update: &Content) -> Result<ContentDiff, UpdateError> {
+        if update.object_id() != self.object_id() {
+            return Err(UpdateError::IdMismatch);
+        }
+
+        if update.author_id() != self.author_id() {
+            return Err(UpdateError::AuthorMismatch);
+        }
+
+        let (stored_poll, updated_poll) = match (self.poll(), update.poll()) {
+            (Some(stored_poll), Some(updated_poll)) => (stored_poll, updated_poll),
+            _ => return Err(UpdateError::NotPoll),
+        };
+
+        if update.last_modified() < self.last_modified() {
+            return Err(UpdateError::Stale {
+                stored: self.last_modified(),
+                update: update.last_modified(),
+            });
+        }
+
+        let same_options = stored_poll.multiple_choice == updated_poll.multiple_choice &&
+            stored_poll.options.len() == updated_poll.options.len() &&
+            stored_poll.options.iter()
+                .zip(updated_poll.options.iter())
+                .all(|(a, b)| a.name == b.name);
+
+        if !same_options {
+            return Err(UpdateError::PollOptionsChanged);
+        }
+
+        if updated_poll.total_votes() < stored_poll.total_votes() {
+            return Err(UpdateError::OutdatedPollResults);
+        }
+
+        let mut updated = self.clone();
+
+        updated.one_of = update.one_of.clone();
+        updated.any_of = update.any_of.clone();
+        updated.end_time = update.end_time.or(self.end_time);
+        updated.closed = update.closed.clone().or_else(|| self.closed.clone());
+        updated.voters_count = update.voters_count;
+
+        let diff = ContentDiff::between(self, &updated);
+        *self = updated;
+
+        Ok(diff)
+    }
+
+    /// Applies poll results from object embedded into `Update` activity,
+    /// see [Content::apply_poll_results()].
+    pub fn apply_poll_results_activity(
+        &mut self,
+        activity: &Activity,
+    ) -> Result<ContentDiff, UpdateError> {
+        let update = self.parse_update_activity(activity)?;
+        self.apply_poll_results(&update)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use crate::content::Content;
+    use crate::update::UpdateError;
+
+    fn question(yes: u64, no: u64, voters: u64) -> Content {
+        serde_json::from_value(serde_json::json!({
+            "id": "https://z.y.x/users/xyz/statuses/1",
+            "type": "Question",
+            "attributedTo": "https://z.y.x/users/xyz",
+            "content": "<p>Cats?</p>",
+            "published": "2024-01-01T00:00:00Z",
+            "endTime": "2024-01-02T00:00:00Z",
+            "closed": "2024-01-02T00:00:00Z",
+            "votersCount": voters,
+            "oneOf": [
+                {
+                    "type": "Note",
+                    "name": "Yes",
+                    "replies": {"type": "Collection", "totalItems": yes}
+                },
+                {
+                    "type": "Note",
+                    "name": "No",
+                    "replies": {"type": "Collection", "totalItems": no}
+                }
+            ]
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_poll_view() {
+        let content = question(3, 1, 4);
+        let poll = content.poll().unwrap();
+
+        assert!(!poll.multiple_choice);
+        assert_eq!(2, poll.options.len());
+        assert_eq!(Some(3), poll.options[0].votes());
+        assert_eq!(4, poll.total_votes());
+        assert_eq!(Some(4), poll.voters());
+
+        assert!(!poll.is_closed(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()));
+        assert!(poll.is_closed(Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_pleroma_multiple_choice_poll() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://pleroma.a.b/objects/1",
+          "type": "Question",
+          "attributedTo": "https://pleroma.a.b/users/cat",
+          "content": "Snacks?",
+          "published": "2024-01-01T00:00:00Z",
+          "oneOf": [],
+          "anyOf": [
+            {"type": "Note", "name": "Fish", "replies": {"type": "Collection", "totalItems": 2}},
+            {"type": "Note", "name": "Milk", "replies": {"type": "Collection", "totalItems": 1}}
+          ]
+        }"#).unwrap();
+
+        let poll = content.poll().unwrap();
+
+        assert!(poll.multiple_choice);
+        assert_eq!(2, poll.options.len());
+        assert_eq!("Fish", poll.options[0].name);
+        assert_eq!(None, poll.voters());
+    }
+
+    #[test]
+    fn test_poll_results_are_applied() {
+        let mut content = question(3, 1, 4);
+
+        let diff = content.apply_poll_results(&question(5, 2, 7)).unwrap();
+
+        assert!(!diff.is_text_changed());
+        assert!(diff.is_changed("votersCount"));
+        assert_eq!(7, content.poll().unwrap().total_votes());
+
+        assert!(matches!(
+            content.apply_poll_results(&question(1, 1, 2)),
+            Err(UpdateError::OutdatedPollResults)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_poll_properties_are_ignored() {
+        let content: Content = serde_json::from_str(r#"{
+          "id": "https://z.y.x/users/xyz/statuses/1",
+          "type": "Question",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "content": "<p>Cats?</p>",
+          "published": "2024-01-01T00:00:00Z",
+          "votersCount": "4",
+          "endTime": "soon",
+          "oneOf": [
+            {"type": "Note", "name": "Yes", "replies": {"type": "Collection", "totalItems": "3"}},
+            {"type": "Note", "name": "No", "replies": {"type": "Collection", "totalItems": 1}}
+          ],
+          "anyOf": [{"type": "Note"}]
+        }"#).unwrap();
+
+        let poll = content.poll().unwrap();
+
+        assert_eq!(None, poll.voters_count);
+        assert_eq!(None, poll.end_time);
+        assert_eq!(None, poll.options[0].votes());
+        assert_eq!(1, poll.total_votes());
+        assert!(content.any_of.is_none());
+    }
+
+    #[test]
+    fn test_missing_poll_end_is_kept() {
+        let mut content = question(3, 1, 4);
+
+        let mut update = question(5, 2, 7);
+        update.end_time = None;
+        update.closed = None;
+
+        content.apply_poll_results(&update).unwrap();
+
+        let poll = content.poll().unwrap();
+
+        assert_eq!(Some(Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()), poll.end_time);
+        assert!(poll.closed.is_some());
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use crate::activity::Activity;
+use crate::content_entities::{emoji_image_url, normalize_shortcode};
+use crate::entity::EntityType;
+use crate::object::ObjectTrait;
+
+/// Emoji used as reaction.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum ReactionEmoji {
+    /// Unicode emoji, e.g. `👍`.
+    Unicode(String),
+
+    /// Custom emoji of some instance.
+    Custom {
+        /// Shortcode without colons and host suffix, e.g. `blobcat`.
+        shortcode: String,
+
+        /// Host of instance emoji belongs to, if given, e.g. `z.y.x`.
+        /// Emoji of different instances could share shortcode.
+        host: Option<String>,
+
+        /// Image to display for emoji, if known.
+        image_url: Option<url::Url>,
+    },
+}
+
+impl ReactionEmoji {
+    /// Parses reaction `value` as sent by Misskey, Pleroma or Akkoma.
+    ///
+    /// `:name:` is a custom emoji, Misskey suffixes its shortcode with
+    /// host, e.g. `:blobcat@z.y.x:`, which is kept, or `:blobcat@.:` for
+    /// local one, which is the same as no suffix. Custom emoji without
+    /// shortcode, e.g. `:@.:`, are rejected. Anything else is treated
+    /// as unicode emoji.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        if value.is_empty() {
+            return None;
+        }
+
+        let is_custom = value.len() > 2 &&
+            value.starts_with(':') &&
+            value.ends_with(':');
+
+        if !is_custom {
+            return Some(ReactionEmoji::Unicode(value.to_string()));
+        }
+
+        let shortcode = normalize_shortcode(value);
+
+        let (shortcode, host) = match shortcode.split_once('@') {
+            None => (shortcode.as_str(), None),
+            Some((name, ".")) => (name, None),
+            Some((name, host)) => (name, Some(host.to_lowercase())),
+        };
+
+        if shortcode.is_empty() || host.as_deref() == Some("") {
+            return None;
+        }
+
+        Some(ReactionEmoji::Custom {
+            shortcode: shortcode.to_string(),
+            host,
+            image_url: None,
+        })
+    }
+
+    /// Returns key identifying emoji regardless of its image,
+    /// e.g. `👍`, `:blobcat:` or `:blobcat@z.y.x:`.
+    pub fn key(&self) -> String {
+        match self {
+            ReactionEmoji::Unicode(emoji) => emoji.clone(),
+
+            ReactionEmoji::Custom { shortcode, host: None, .. } =>
+                format!(":{shortcode}:"),
+
+            ReactionEmoji::Custom { shortcode, host: Some(host), .. } =>
+                format!(":{shortcode}@{host}:"),
+        }
+    }
+}
+
+/// Emoji reaction of actor to object.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Reaction {
+    /// Who reacts.
+    pub actor: url::Url,
+
+    /// Object reacted to.
+    pub object: url::Url,
+
+    /// Emoji of reaction.
+    pub emoji: ReactionEmoji,
+
+    /// `true` if reaction is retracted with `Undo`.
+    pub retracted: bool,
+}
+
+impl Reaction {
+    /// Converts `EmojiReact`, `Like` with `content` or `_misskey_reaction`,
+    /// or `Undo` of those activities to reaction.
+    /// Plain `Like` is a favourite, not a reaction, so it is ignored.
+    /// Undone reaction must be made by actor of `Undo`.
+    pub fn from_activity(activity: &Activity) -> Option<Self> {
+        match activity.entity_type() {
+            EntityType::Undo => {
+                let inner: Activity = serde_json::from_value(activity.object.clone()).ok()?;
+                let reaction = Self::from_activity(&inner)?;
+
+                if reaction.retracted || Some(&reaction.actor) != activity.actor.id() {
+                    return None;
+                }
+
+                Some(Self {
+                    retracted: true,
+                    ..reaction
+                })
+            }
+
+            EntityType::EmojiReact |
+            EntityType::Like => {
+                let value = activity.misskey_reaction.as_deref()
+                    .or(activity.content.as_deref())?;
+
+                Some(Self {
+                    actor: activity.actor.id()?.clone(),
+                    object: activity.inner_object_id()?,
+                    emoji: Self::resolve_image(ReactionEmoji::parse(value)?, activity),
+                    retracted: false,
+                })
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Sets image of custom emoji from `Emoji` tag of `activity`.
+    fn resolve_image(emoji: ReactionEmoji, activity: &Activity) -> ReactionEmoji {
+        let (shortcode, host) = match emoji {
+            ReactionEmoji::Unicode(_) => return emoji,
+            ReactionEmoji::Custom { shortcode, host, .. } => (shortcode, host),
+        };
+
+        // tags name emoji by shortcode only, e.g. `:blobcat:`.
+        let image_url = activity.tag.iter()
+            .flat_map(|reference| reference.as_vec())
+            .filter(|tag| tag.entity_type() == EntityType::Emoji)
+            .filter(|tag| tag.name.as_deref()
+                .map(|name| normalize_shortcode(name) == shortcode)
+                .unwrap_or(false)
+            )
+            .find_map(emoji_image_url);
+
+        ReactionEmoji::Custom {
+            shortcode,
+            host,
+            image_url,
+        }
+    }
+}
+
+/// Number of reactions with the same emoji to an object.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReactionCount {
+    /// Emoji of reactions.
+    pub emoji: ReactionEmoji,
+
+    /// Actors who reacted, in order of reactions.
+    pub actors: Vec<url::Url>,
+}
+
+impl ReactionCount {
+    /// Returns number of reactions.
+    pub fn count(&self) -> usize {
+        self.actors.len()
+    }
+}
+
+/// Aggregates `reactions` per object, in order given.
+///
+/// Each actor is counted once per emoji, retracted reactions cancel
+/// earlier ones. Emoji of each object are in order of first reaction,
+/// ones without reactions left are dropped.
+pub fn aggregate_reactions<'a>(
+    reactions: impl IntoIterator<Item = &'a Reaction>
+) -> HashMap<url::Url, Vec<ReactionCount>> {
+    let mut aggregated: HashMap<url::Url, Vec<ReactionCount>> = HashMap::new();
+
+    for reaction in reactions {
+        let counts = aggregated.entry(reaction.object.clone()).or_default();
+        let key = reaction.emoji.key();
+
+        let position = counts.iter()
+            .position(|count| count.emoji.key() == key);
+
+        let count = match position {
+            Some(position) => &mut counts[position],
+
+            None => {
+                counts.push(ReactionCount {
+                    emoji: reaction.emoji.clone(),
+                    actors: vec![],
+                });
+
+                counts.last_mut().unwrap()
+            }
+        };
+
+        // image could be missing in some of reactions
+        if let (
+            ReactionEmoji::Custom { image_url: image_url @ None, .. },
+            ReactionEmoji::Custom { image_url: Some(url), .. },
+        ) = (&mut count.emoji, &reaction.emoji) {
+            *image_url = Some(url.clone());
+        }
+
+        let known = count.actors.contains(&reaction.actor);
+
+        match (reaction.retracted, known) {
+            (false, false) => count.actors.push(reaction.actor.clone()),
+            (true, true) => count.actors.retain(|actor| actor != &reaction.actor),
+            _ => {}
+        }
+    }
+
+    for counts in aggregated.values_mut() {
+        counts.retain(|count| count.count() > 0);
+    }
+
+    aggregated.retain(|_, counts| !counts.is_empty());
+    aggregated
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::activity::Activity;
+    use crate::reaction::{aggregate_reactions, Reaction, ReactionEmoji};
+
+    fn reaction(json: &str) -> Option<Reaction> {
+        let activity: Activity = serde_json::from_str(json).unwrap();
+        Reaction::from_activity(&activity)
+    }
+
+    #[test]
+    fn test_reactions() {
+        let misskey = reaction(r#"{
+          "type": "Like",
+          "id": "https://misskey.z.y.x/likes/1",
+          "actor": "https://misskey.z.y.x/users/cat",
+          "object": "https://z.y.x/notes/1",
+          "content": ":blobcat@.:",
+          "_misskey_reaction": ":blobcat@.:",
+          "tag": [
+            {
+              "type": "Emoji",
+              "id": "https://misskey.z.y.x/emojis/blobcat",
+              "name": ":blobcat:",
+              "icon": {
+                "type": "Image",
+                "url": "https://misskey.z.y.x/files/blobcat.png"
+              }
+            }
+          ]
+        }"#).unwrap();
+
+        assert_eq!(":blobcat:", misskey.emoji.key());
+
+        assert_eq!(
+            ReactionEmoji::Custom {
+                shortcode: "blobcat".to_string(),
+                host: None,
+                image_url: Some(url::Url::parse("https://misskey.z.y.x/files/blobcat.png").unwrap()),
+            },
+            misskey.emoji
+        );
+
+        let pleroma = reaction(r#"{
+          "type": "EmojiReact",
+          "id": "https://pleroma.a.b/activities/1",
+          "actor": "https://pleroma.a.b/users/dog",
+          "object": "https://z.y.x/notes/1",
+          "content": "👍"
+        }"#).unwrap();
+
+        assert_eq!(ReactionEmoji::Unicode("👍".to_string()), pleroma.emoji);
+
+        let undo = reaction(r#"{
+          "type": "Undo",
+          "id": "https://pleroma.a.b/activities/2",
+          "actor": "https://pleroma.a.b/users/dog",
+          "object": {
+            "type": "EmojiReact",
+            "id": "https://pleroma.a.b/activities/1",
+            "actor": "https://pleroma.a.b/users/dog",
+            "object": "https://z.y.x/notes/1",
+            "content": "👍"
+          }
+        }"#).unwrap();
+
+        assert!(undo.retracted);
+
+        let favourite = reaction(r#"{
+          "type": "Like",
+          "id": "https://z.y.x/likes/1",
+          "actor": "https://z.y.x/users/xyz",
+          "object": "https://z.y.x/notes/1"
+        }"#);
+
+        assert!(favourite.is_none());
+
+        let aggregated = aggregate_reactions(&[
+            misskey.clone(),
+            pleroma.clone(),
+            misskey,
+            pleroma,
+            undo,
+        ]);
+
+        let counts = &aggregated[&url::Url::parse("https://z.y.x/notes/1").unwrap()];
+
+        assert_eq!(1, counts.len());
+        assert_eq!(":blobcat:", counts[0].emoji.key());
+        assert_eq!(1, counts[0].count());
+    }
+
+    #[test]
+    fn test_custom_emoji_of_different_hosts_are_distinct() {
+        let local = ReactionEmoji::parse(":blobcat@.:").unwrap();
+        let remote = ReactionEmoji::parse(":blobcat@Z.y.x:").unwrap();
+
+        assert_eq!(":blobcat:", local.key());
+        assert_eq!(local.key(), ReactionEmoji::parse(":blobcat:").unwrap().key());
+        assert_eq!(":blobcat@z.y.x:", remote.key());
+        assert_ne!(local.key(), remote.key());
+
+        assert_eq!(None, ReactionEmoji::parse(":@:"));
+        assert_eq!(None, ReactionEmoji::parse(":@.:"));
+        assert_eq!(None, ReactionEmoji::parse(":blobcat@:"));
+    }
+}
@@ -0,0 +1,372 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::content::{deserialize_lenient, Content};
+use crate::entity::UnknownProperties;
+
+/// Media type of Misskey Flavored Markdown.
+pub const MFM_MEDIA_TYPE: &str = "text/x.misskeymarkdown";
+
+/// Media type of Markdown.
+pub const MARKDOWN_MEDIA_TYPE: &str = "text/markdown";
+
+/// Media type of plain text.
+pub const PLAIN_TEXT_MEDIA_TYPE: &str = "text/plain";
+
+/// Markup languages of source this crate knows how to convert to plain text.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SourceMarkup {
+    /// Misskey Flavored Markdown.
+    /// See: <https://misskey-hub.net/en/docs/for-users/features/mfm/>
+    Mfm,
+
+    /// Markdown.
+    Markdown,
+
+    /// Plain text.
+    PlainText,
+}
+
+impl SourceMarkup {
+    /// Converts `media_type` to [SourceMarkup] if it is known.
+    /// Media type parameters, e.g. `charset`, are ignored.
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        match essence.as_str() {
+            MFM_MEDIA_TYPE => Some(Self::Mfm),
+            MARKDOWN_MEDIA_TYPE => Some(Self::Markdown),
+            PLAIN_TEXT_MEDIA_TYPE => Some(Self::PlainText),
+            _ => None,
+        }
+    }
+}
+
+/// Source content was rendered from.
+/// See: <https://www.w3.org/TR/activitypub/#source-property>
+///
+/// Example:
+/// ```json
+/// "source": {
+///     "content": "$[rainbow :arisa_fuo_1:] xyz",
+///     "mediaType": "text/x.misskeymarkdown"
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Source {
+    /// Source markup itself.
+    pub content: String,
+
+    /// Media type of markup, e.g. `text/markdown`.
+    #[serde(rename = "mediaType")]
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    /// Properties not modelled by this crate.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+impl Source {
+    /// Returns markup language of this source if it is known.
+    pub fn markup(&self) -> Option<SourceMarkup> {
+        self.media_type.as_deref()
+            .and_then(SourceMarkup::from_media_type)
+    }
+
+    /// Converts source to plain text if its markup is known.
+    pub fn to_plain_text(&self) -> Option<String> {
+        self.markup()
+            .map(|markup| match markup {
+                SourceMarkup::Mfm => mfm_to_text(&self.content),
+                SourceMarkup::Markdown => markdown_to_text(&self.content),
+                SourceMarkup::PlainText => self.content.clone(),
+            })
+    }
+}
+
+/// Helper enumeration to deal with different types of `source` property.
+/// Older Pleroma versions and some other software send source markup
+/// as plain string, without media type.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SourceReference {
+    /// Source with media type, as ActivityPub defines it.
+    Source(Source),
+
+    /// Just source markup.
+    Text(String),
+}
+
+impl SourceReference {
+    /// Returns source markup itself.
+    pub fn content(&self) -> &str {
+        match self {
+            SourceReference::Source(source) => &source.content,
+            SourceReference::Text(text) => text,
+        }
+    }
+
+    /// Returns markup language of source if it is known,
+    /// it is never known for plain string.
+    pub fn markup(&self) -> Option<SourceMarkup> {
+        match self {
+            SourceReference::Source(source) => source.markup(),
+            SourceReference::Text(_) => None,
+        }
+    }
+
+    /// Converts source to plain text if its markup is known.
+    pub fn to_plain_text(&self) -> Option<String> {
+        match self {
+            SourceReference::Source(source) => source.to_plain_text(),
+            SourceReference::Text(_) => None,
+        }
+    }
+}
+
+/// MFM tags that are not HTML-like, e.g. `<center>`.
+static MFM_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"</?(center|small|plain|i|b|s)>").unwrap()
+});
+
+/// Converts Misskey Flavored Markdown to plain text.
+///
+/// MFM functions, e.g. `$[rainbow text]`, are replaced with their
+/// content, then remaining markup is processed as Markdown.
+/// Custom emoji shortcodes, mentions and hashtags are kept as is.
+pub fn mfm_to_text(mfm: &str) -> String {
+    let mut text = String::with_capacity(mfm.len());
+    // open brackets, `true` for ones of functions, so `]` of nested
+    // Markdown link does not close function.
+    let mut brackets: Vec<bool> = vec![];
+    let mut chars = mfm.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        if c == '$' && mfm[position..].starts_with("$[") {
+            let rest = &mfm[position + 2..];
+
+            // function name with optional parameters,
+            // e.g. `spin.x,speed=2s`, is terminated with space.
+            let name_length = rest.find(|c: char| !is_mfm_function_char(c));
+
+            if let Some(name_length) = name_length.filter(|length| *length > 0) {
+                if rest[name_length..].starts_with(' ') {
+                    brackets.push(true);
+
+                    // skip `[`, name and space
+                    for _ in 0..name_length + 2 {
+                        chars.next();
+                    }
+
+                    continue;
+                }
+            }
+        }
+
+        match c {
+            '[' => brackets.push(false),
+            ']' if brackets.pop() == Some(true) => continue,
+            _ => {}
+        }
+
+        text.push(c);
+    }
+
+    let text = MFM_TAG.replace_all(&text, "");
+
+    markdown_to_text(&text)
+}
+
+/// Returns `true` if `c` could be part of MFM function name or parameters.
+fn is_mfm_function_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | ',' | '=' | '_' | '-' | '+')
+}
+
+/// Markdown inline markup, each expression keeps text in `text` group.
+static MARKDOWN_INLINE: Lazy<Vec<Regex>> = Lazy::new(|| {
+    vec![
+        Regex::new(r"!\[(?P<text>[^\]]*)\]\([^)]*\)").unwrap(),
+        Regex::new(r"\??\[(?P<text>[^\]]*)\]\([^)]*\)").unwrap(),
+        Regex::new(r"<(?P<text>https?://[^>\s]+)>").unwrap(),
+        Regex::new(r"\*\*(?P<text>[^*]+?)\*\*").unwrap(),
+        Regex::new(r"__(?P<text>[^_]+?)__").unwrap(),
+        Regex::new(r"~~(?P<text>[^~]+?)~~").unwrap(),
+        Regex::new(r"\*(?P<text>[^*\s][^*]*?)\*").unwrap(),
+        Regex::new(r"`(?P<text>[^`]+)`").unwrap(),
+    ]
+});
+
+/// Markdown block markup at the beginning of line.
+static MARKDOWN_LINE_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(#{1,6}\s+|(>\s?)+|[-*+]\s+|\d+\.\s+)").unwrap()
+});
+
+/// Converts Markdown to plain text.
+///
+/// It is not a complete Markdown parser, just enough to remove common
+/// markup: headings, quotes, list markers, emphasis, code and links.
+/// Links are replaced with their text. Single underscore emphasis is
+/// kept as is, so `snake_case` words are not mangled.
+pub fn markdown_to_text(markdown: &str) -> String {
+    let lines: Vec<String> = markdown.lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .map(|line| {
+            let mut line = MARKDOWN_LINE_PREFIX.replace(line, "")
+                .to_string();
+
+            for regex in MARKDOWN_INLINE.iter() {
+                line = regex.replace_all(&line, "$text").to_string();
+            }
+
+            line
+        })
+        .collect();
+
+    lines.join("\n")
+}
+
+impl Content {
+    /// Returns the best plain text representation of this content.
+    ///
+    /// Source markup of known media type is preferred over HTML, then
+    /// Misskey's `_misskey_content`, then `content` and `name` HTML
+    /// processed with `cleaner` function.
+    pub fn plain_text(&self, cleaner: &dyn Fn(&str) -> String) -> Option<String> {
+        if let Some(text) = self.source.as_ref()
+            .and_then(|source| source.to_plain_text()) {
+            return Some(text);
+        }
+
+        if let Some(misskey_content) = &self.misskey_content {
+            return Some(mfm_to_text(misskey_content));
+        }
+
+        self.content.as_ref()
+            .or(self.object_entity.name.as_ref())
+            .map(|html| cleaner(html))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::Content;
+    use crate::source::{markdown_to_text, mfm_to_text, SourceMarkup};
+
+    #[test]
+    fn test_mfm_to_text() {
+        assert_eq!(
+            ":arisa_fuo_1: xyz\nhttps://live-theater.net/play/zzzzzzz",
+            mfm_to_text("$[rainbow :arisa_fuo_1:] xyz\nhttps://live-theater.net/play/zzzzzzz")
+        );
+
+        assert_eq!(
+            "fast spin and bold",
+            mfm_to_text("$[spin.speed=0.5s $[x2 fast spin]] and **bold**")
+        );
+
+        assert_eq!(
+            "centered [not a function]",
+            mfm_to_text("<center>centered</center> [not a function]")
+        );
+
+        assert_eq!(
+            "big link and [brackets] too",
+            mfm_to_text("$[x2 big [link](https://a.b)] and $[fg.color=f00 [brackets]] too")
+        );
+    }
+
+    #[test]
+    fn test_markdown_to_text() {
+        assert_eq!(
+            "Title\nquote\nitem with link and code\nsnake_case_name",
+            markdown_to_text(
+                "# Title\n> quote\n- item with [link](https://x.y) and `code`\n```\nsnake_case_name"
+            )
+        );
+    }
+
+    #[test]
+    fn test_source_is_preferred_over_html() {
+        let content: Content = serde_json::from_str(r#"{
+            "id": "https://x.y/notes/1",
+            "type": "Note",
+            "attributedTo": "https://x.y/users/xyz",
+            "content": "<p>Hello <b>world</b></p>",
+            "source": {
+                "content": "Hello **world**",
+                "mediaType": "text/markdown; charset=utf-8"
+            },
+            "published": "2024-01-01T01:01:01Z"
+        }"#).unwrap();
+
+        let source = content.source.as_ref().unwrap();
+
+        assert_eq!(Some(SourceMarkup::Markdown), source.markup());
+
+        let cleaner = |html: &str| html.to_string();
+
+        assert_eq!(
+            Some("Hello world".to_string()),
+            content.plain_text(&cleaner)
+        );
+    }
+
+    #[test]
+    fn test_plain_string_source() {
+        let content: Content = serde_json::from_str(r#"{
+            "id": "https://x.y/objects/1",
+            "type": "Note",
+            "attributedTo": "https://x.y/users/xyz",
+            "content": "<p>Hello <b>world</b></p>",
+            "source": "Hello **world**",
+            "published": "2024-01-01T01:01:01Z"
+        }"#).unwrap();
+
+        let source = content.source.as_ref().unwrap();
+
+        assert_eq!("Hello **world**", source.content());
+        assert_eq!(None, source.markup());
+
+        // markup of plain string source is unknown, so HTML is used
+        let cleaner = |html: &str| html.replace("<p>", "").replace("</p>", "");
+
+        assert_eq!(
+            Some("Hello <b>world</b>".to_string()),
+            content.plain_text(&cleaner)
+        );
+    }
+
+    #[test]
+    fn test_malformed_source_is_ignored() {
+        let content: Content = serde_json::from_str(r#"{
+            "id": "https://x.y/notes/1",
+            "type": "Note",
+            "attributedTo": "https://x.y/users/xyz",
+            "content": "<p>Hello</p>",
+            "source": {"mediaType": "text/markdown"},
+            "_misskey_content": ["Hello"],
+            "published": "2024-01-01T01:01:01Z"
+        }"#).unwrap();
+
+        assert!(content.source.is_none());
+        assert!(content.misskey_content.is_none());
+
+        let content: Content = serde_json::from_str(r#"{
+            "id": "https://x.y/notes/1",
+            "type": "Note",
+            "attributedTo": "https://x.y/users/xyz",
+            "content": "<p>Hello</p>",
+            "source": {"content": "Hello", "mediaType": 1},
+            "published": "2024-01-01T01:01:01Z"
+        }"#).unwrap();
+
+        assert_eq!(None, content.source.unwrap().markup());
+    }
+}
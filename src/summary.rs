@@ -0,0 +1,164 @@
+use crate::content::Content;
+use crate::entity::EntityType;
+use crate::nodeinfo::ServerSoftware;
+use crate::object::ObjectTrait;
+
+/// Meaning of `summary` property of content.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SummaryKind {
+    /// Summary is a content warning, Mastodon's way.
+    /// Content is expected to be hidden behind it.
+    ContentWarning,
+
+    /// Summary is an excerpt or a lead of article.
+    Excerpt,
+}
+
+impl Content {
+    /// Guesses software content is published by, using `@context`
+    /// and Misskey specific properties as cues.
+    pub fn guess_server_software(&self) -> Option<ServerSoftware> {
+        let from_context = self.context()
+            .and_then(ServerSoftware::guess_from_context);
+
+        if self.misskey_content.is_some() || self.misskey_quote.is_some() {
+            return Some(from_context
+                .filter(|software| software.is_misskey_like())
+                .unwrap_or(ServerSoftware::Misskey)
+            );
+        }
+
+        from_context
+    }
+
+    /// Returns meaning of `summary` property if it is set,
+    /// software is guessed with [Content::guess_server_software()].
+    pub fn summary_kind(&self) -> Option<SummaryKind> {
+        self.summary_kind_for(self.guess_server_software())
+    }
+
+    /// Returns meaning of `summary` property if it is set, assuming
+    /// content is published by `software`, e.g. learnt from NodeInfo.
+    ///
+    /// Summary of sensitive content is always a content warning.
+    /// Otherwise, it is an excerpt for `Article`, `Event` and `Page` objects
+    /// and for blogging software, such as WordPress, and a content warning
+    /// for everything else, as Mastodon and Misskey treat it.
+    pub fn summary_kind_for(&self, software: Option<ServerSoftware>) -> Option<SummaryKind> {
+        let summary = self.summary.as_deref()?;
+
+        if summary.trim().is_empty() {
+            return None;
+        }
+
+        if self.sensitive == Some(true) {
+            return Some(SummaryKind::ContentWarning);
+        }
+
+        let misskey_like = software
+            .map(|software| software.is_misskey_like())
+            .unwrap_or(false);
+
+        let kind = match self.entity_type() {
+            EntityType::Article |
+            EntityType::Event |
+            EntityType::Page if !misskey_like => SummaryKind::Excerpt,
+
+            _ => match software {
+                Some(ServerSoftware::WordPress) |
+                Some(ServerSoftware::WriteFreely) => SummaryKind::Excerpt,
+                _ => SummaryKind::ContentWarning,
+            }
+        };
+
+        Some(kind)
+    }
+
+    /// Returns content warning, if `summary` is one.
+    pub fn content_warning(&self) -> Option<&str> {
+        match self.summary_kind()? {
+            SummaryKind::ContentWarning => self.summary.as_deref(),
+            SummaryKind::Excerpt => None,
+        }
+    }
+
+    /// Returns excerpt of content, if `summary` is one.
+    pub fn excerpt(&self) -> Option<&str> {
+        match self.summary_kind()? {
+            SummaryKind::ContentWarning => None,
+            SummaryKind::Excerpt => self.summary.as_deref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::{Content, ContentMapOptions, ContentWarningMode};
+    use crate::nodeinfo::ServerSoftware;
+    use crate::summary::SummaryKind;
+
+    fn content(object_type: &str, sensitive: bool) -> Content {
+        serde_json::from_value(serde_json::json!({
+            "id": "https://z.y.x/users/xyz/statuses/1",
+            "type": object_type,
+            "attributedTo": "https://z.y.x/users/xyz",
+            "published": "2024-01-01T00:00:00Z",
+            "sensitive": sensitive,
+            "summary": "Spoilers",
+            "content": "<p>Text</p>"
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_summary_kind() {
+        assert_eq!(Some("Spoilers"), content("Note", false).content_warning());
+        assert_eq!(None, content("Note", false).excerpt());
+        assert_eq!(Some("Spoilers"), content("Article", false).excerpt());
+        assert_eq!(Some("Spoilers"), content("Article", true).content_warning());
+
+        assert_eq!(
+            Some(SummaryKind::Excerpt),
+            content("Note", false).summary_kind_for(Some(ServerSoftware::WordPress))
+        );
+    }
+
+    #[test]
+    fn test_content_warning_modes() {
+        let cleaner = |v: &str| v.to_string();
+        let note = content("Note", true);
+
+        let options = |content_warning| ContentMapOptions { content_warning };
+
+        let prepended = note.get_content_map_with_options(
+            &cleaner,
+            &options(ContentWarningMode::Prepend)
+        ).unwrap();
+
+        assert_eq!("<p>Spoilers</p>\n<p>Text</p>", prepended.content["default"]);
+        assert!(prepended.content_warnings.is_empty());
+
+        let excluded = note.get_content_map_with_options(
+            &cleaner,
+            &options(ContentWarningMode::Exclude)
+        ).unwrap();
+
+        assert_eq!("<p>Text</p>", excluded.content["default"]);
+        assert!(excluded.content_warnings.is_empty());
+
+        let separate = note.get_content_map_with_options(
+            &cleaner,
+            &options(ContentWarningMode::Separate)
+        ).unwrap();
+
+        assert_eq!("<p>Text</p>", separate.content["default"]);
+        assert_eq!("Spoilers", separate.content_warnings["default"]);
+
+        // excerpts are kept in text regardless of mode
+        let article = content("Article", false).get_content_map_with_options(
+            &cleaner,
+            &options(ContentWarningMode::Exclude)
+        ).unwrap();
+
+        assert_eq!("<p>Spoilers</p>\n<p>Text</p>", article.content["default"]);
+    }
+}
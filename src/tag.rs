@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use crate::entity::{Entity, EntityType};
 use crate::image::ImageReference;
-use crate::object::UrlReference;
+use crate::object::{is_activitystreams_media_type, UrlReference};
 
 /// This structure is used to deserialize Tag object.
 /// Despite its name that way, it could store mentions,
@@ -15,18 +15,27 @@ pub struct Tag {
 
     /// Reference to Tag related details, e.g. list of posts for this tag.
     #[serde(alias = "href")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<UrlReference>,
 
     /// Name of tag, e.g. `#tag`.
     #[serde(alias = "tag")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     #[cfg(feature = "more_properties")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<url::Url>,
 
     /// For `Emoji` references image to display for emoji.
     #[serde(alias = "image")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<ImageReference>,
+
+    /// Media type of referenced resource, it is set for `Link` tags.
+    #[serde(rename = "mediaType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
 }
 
 impl Tag {
@@ -38,6 +47,17 @@ impl Tag {
     pub fn entity_type(&self) -> EntityType {
         self.entity.object_type
     }
+
+    /// Returns `true` if this tag is FEP-e232 object link, that is `Link`
+    /// to another ActivityPub object. Misskey and others use it to
+    /// reference quoted posts.
+    /// See: <https://codeberg.org/fediverse/fep/src/branch/main/fep/e232/fep-e232.md>
+    pub fn is_object_link(&self) -> bool {
+        self.entity_type() == EntityType::Link &&
+            self.media_type.as_deref()
+                .map(is_activitystreams_media_type)
+                .unwrap_or(false)
+    }
 }
 
 /// Helper enumeration to wrap different variants of `tag` property.
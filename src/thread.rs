@@ -0,0 +1,437 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::content::Content;
+use crate::object::ObjectTrait;
+
+/// Node of reply tree: content item with replies to it.
+///
+/// Cloning, formatting and dropping walk the tree with explicit stack,
+/// so deep threads do not overflow call stack. As node implements
+/// [Drop], use [ThreadNode::into_parts()] to move its fields out.
+pub struct ThreadNode<'a> {
+    /// Content item itself.
+    pub content: &'a Content,
+
+    /// Replies to this item ordered by `published`.
+    pub replies: Vec<ThreadNode<'a>>,
+}
+
+impl<'a> ThreadNode<'a> {
+    /// Returns number of items in this subtree, including this node.
+    pub fn size(&self) -> usize {
+        let mut size = 0;
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            size += 1;
+            stack.extend(node.replies.iter());
+        }
+
+        size
+    }
+
+    /// Returns all content items of this subtree in depth-first order.
+    ///
+    /// Trees are walked with explicit stack, so deep threads do not
+    /// overflow call stack.
+    pub fn flatten(&self) -> Vec<&'a Content> {
+        let mut items = vec![];
+        let mut stack = vec![self];
+
+        while let Some(node) = stack.pop() {
+            items.push(node.content);
+            stack.extend(node.replies.iter().rev());
+        }
+
+        items
+    }
+
+    /// Consumes node and returns its content item and replies.
+    pub fn into_parts(mut self) -> (&'a Content, Vec<ThreadNode<'a>>) {
+        let replies = std::mem::take(&mut self.replies);
+        (self.content, replies)
+    }
+}
+
+impl Clone for ThreadNode<'_> {
+    fn clone(&self) -> Self {
+        let shell = |node: &Self| ThreadNode {
+            content: node.content,
+            replies: Vec::with_capacity(node.replies.len()),
+        };
+
+        // source node, its clone and index of the next reply to clone
+        let mut stack = vec![(self, shell(self), 0)];
+
+        loop {
+            let (source, _, index) = stack.last_mut()
+                .expect("stack is not empty");
+
+            if let Some(reply) = source.replies.get(*index) {
+                *index += 1;
+                stack.push((reply, shell(reply), 0));
+                continue;
+            }
+
+            let (_, node, _) = stack.pop()
+                .expect("stack is not empty");
+
+            match stack.last_mut() {
+                Some((_, parent, _)) => parent.replies.push(node),
+                None => return node,
+            }
+        }
+    }
+}
+
+/// Debug trait implementation that lists IDs of subtree items
+/// with their depth in depth-first order.
+impl std::fmt::Debug for ThreadNode<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut items = vec![];
+        let mut stack = vec![(self, 0usize)];
+
+        while let Some((node, depth)) = stack.pop() {
+            items.push((depth, node.content.object_id_str()));
+            stack.extend(node.replies.iter().rev().map(|reply| (reply, depth + 1)));
+        }
+
+        f.debug_struct("ap::ThreadNode")
+            .field("items", &items)
+            .finish()
+    }
+}
+
+impl Drop for ThreadNode<'_> {
+    // Replies are detached before they are dropped, so dropping
+    // deep threads does not recurse either.
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.replies);
+
+        while let Some(mut node) = stack.pop() {
+            stack.append(&mut node.replies);
+        }
+    }
+}
+
+/// Root of reply tree that could not be attached to any parent.
+#[derive(Debug, Clone)]
+pub struct ThreadRoot<'a> {
+    /// Tree itself.
+    pub node: ThreadNode<'a>,
+
+    /// ID of parent this root replies to, if parent is not in
+    /// the set of items tree is built from.
+    pub missing_parent: Option<&'a url::Url>,
+
+    /// Set if this root is a member of reply cycle and was detached
+    /// from its parent to break the cycle.
+    pub breaks_cycle: bool,
+}
+
+/// Set of reply trees sharing the same conversation.
+#[derive(Debug, Clone)]
+pub struct Conversation<'a> {
+    /// Conversation identifier as returned by [Content::conversation_id()].
+    /// Trees that have no conversation identifier are grouped under `None`.
+    pub id: Option<&'a str>,
+
+    /// Reply trees of this conversation ordered by `published`.
+    pub roots: Vec<ThreadRoot<'a>>,
+}
+
+/// Result of thread reconstruction.
+#[derive(Debug, Clone)]
+pub struct Threads<'a> {
+    /// Conversations ordered by `published` of the earliest root.
+    pub conversations: Vec<Conversation<'a>>,
+
+    /// IDs of referenced parents that are absent in the set of items.
+    pub missing_parents: Vec<&'a url::Url>,
+
+    /// Detected reply cycles, each is a list of item IDs forming cycle.
+    pub cycles: Vec<Vec<&'a url::Url>>,
+}
+
+/// Builds reply trees from given `items`.
+///
+/// Items are linked with `inReplyTo`, siblings are ordered by `published`
+/// and then by ID to keep order stable. Items replying to objects that
+/// are not in `items` become roots with [ThreadRoot::missing_parent] set.
+/// Reply cycles are broken by detaching the earliest published item of
+/// the cycle from its parent.
+///
+/// Trees are grouped into conversations by `context` or `conversation`
+/// property of root. If root does not declare conversation, the first
+/// declared by any item of tree is used. Items with duplicate IDs are
+/// ignored except the first one.
+pub fn build_threads(items: &[Content]) -> Threads<'_> {
+    let mut by_id: HashMap<&str, &Content> = HashMap::new();
+
+    for item in items.iter() {
+        by_id.entry(item.object_id_str())
+            .or_insert(item);
+    }
+
+    let mut unique: Vec<&Content> = by_id.values()
+        .copied()
+        .collect();
+
+    sort_by_published(&mut unique);
+
+    let parent_of = |item: &Content| -> Option<&Content> {
+        item.reply_target()
+            .and_then(|target| by_id.get(target.as_str()))
+            .copied()
+            .filter(|parent| parent.object_id() != item.object_id())
+    };
+
+    // Cycles are searched by walking up parent links from every item.
+    let mut cycle_breakers: HashSet<&str> = HashSet::new();
+    let mut cycles: Vec<Vec<&url::Url>> = vec![];
+    let mut visited: HashSet<&str> = HashSet::new();
+
+    for item in unique.iter() {
+        let mut path: Vec<&Content> = vec![];
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        let mut current = Some(*item);
+
+        while let Some(node) = current {
+            let id = node.object_id_str();
+
+            if let Some(position) = positions.get(id).copied() {
+                let mut cycle: Vec<&Content> = path[position..].to_vec();
+                sort_by_published(&mut cycle);
+
+                cycle_breakers.insert(cycle[0].object_id_str());
+                cycles.push(cycle.iter().map(|x| x.object_id()).collect());
+                break;
+            }
+
+            if !visited.insert(id) {
+                break;
+            }
+
+            positions.insert(id, path.len());
+            path.push(node);
+            current = parent_of(node);
+        }
+    }
+
+    let mut children: HashMap<&str, Vec<&Content>> = HashMap::new();
+    let mut roots: Vec<&Content> = vec![];
+
+    for item in unique.iter() {
+        let parent = parent_of(item)
+            .filter(|_| !cycle_breakers.contains(item.object_id_str()));
+
+        match parent {
+            Some(parent) => children.entry(parent.object_id_str())
+                .or_default()
+                .push(item),
+
+            None => roots.push(item),
+        }
+    }
+
+    let mut missing_parents: Vec<&url::Url> = vec![];
+    let mut conversations: Vec<Conversation> = vec![];
+    let mut conversation_index: HashMap<Option<&str>, usize> = HashMap::new();
+
+    for root in roots.into_iter() {
+        let missing_parent = root.reply_target()
+            .filter(|target| !by_id.contains_key(target.as_str()));
+
+        if let Some(target) = missing_parent {
+            if !missing_parents.contains(&target) {
+                missing_parents.push(target);
+            }
+        }
+
+        let node = build_node(root, &children);
+
+        let conversation_id = node.flatten()
+            .into_iter()
+            .find_map(|item| item.conversation_id());
+
+        let thread_root = ThreadRoot {
+            node,
+            missing_parent,
+            breaks_cycle: cycle_breakers.contains(root.object_id_str()),
+        };
+
+        match conversation_index.get(&conversation_id) {
+            Some(index) => conversations[*index].roots.push(thread_root),
+
+            None => {
+                conversation_index.insert(conversation_id, conversations.len());
+
+                conversations.push(Conversation {
+                    id: conversation_id,
+                    roots: vec![thread_root],
+                });
+            }
+        }
+    }
+
+    Threads {
+        conversations,
+        missing_parents,
+        cycles,
+    }
+}
+
+/// Builds [ThreadNode] for `item` using `children` index.
+///
+/// Nodes are built with explicit stack of nodes under construction,
+/// each with position of the next reply to descend into.
+fn build_node<'a>(
+    item: &'a Content,
+    children: &HashMap<&str, Vec<&'a Content>>,
+) -> ThreadNode<'a> {
+    let replies_of = |item: &Content| children.get(item.object_id_str())
+        .map(|replies| replies.as_slice())
+        .unwrap_or_default();
+
+    let leaf = |content: &'a Content| ThreadNode {
+        content,
+        replies: vec![],
+    };
+
+    let mut stack: Vec<(ThreadNode<'a>, usize)> = vec![(leaf(item), 0)];
+
+    loop {
+        // root is popped last and returned, so stack is never empty here.
+        let (node, next) = stack.pop().unwrap();
+
+        if let Some(reply) = replies_of(node.content).get(next) {
+            stack.push((node, next + 1));
+            stack.push((leaf(reply), 0));
+            continue;
+        }
+
+        match stack.last_mut() {
+            Some((parent, _)) => parent.replies.push(node),
+            None => return node,
+        }
+    }
+}
+
+/// Sorts `items` by `published` and then by ID.
+fn sort_by_published(items: &mut [&Content]) {
+    items.sort_by(|a, b| a.published.cmp(&b.published)
+        .then_with(|| a.object_id_str().cmp(b.object_id_str()))
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::content::Content;
+    use crate::object::ObjectTrait;
+    use crate::thread::build_threads;
+
+    fn note(id: &str, published: &str, in_reply_to: Option<&str>) -> Content {
+        let mut value = serde_json::json!({
+            "id": format!("https://x.y/notes/{id}"),
+            "type": "Note",
+            "attributedTo": "https://x.y/users/xyz",
+            "content": id,
+            "published": published,
+            "context": "https://x.y/contexts/1",
+        });
+
+        if let Some(parent) = in_reply_to {
+            value["inReplyTo"] = format!("https://x.y/notes/{parent}").into();
+        }
+
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_reply_tree_is_built() {
+        let items = vec![
+            note("c", "2024-01-01T00:03:00Z", Some("a")),
+            note("b", "2024-01-01T00:02:00Z", Some("a")),
+            note("a", "2024-01-01T00:01:00Z", None),
+            note("d", "2024-01-01T00:04:00Z", Some("b")),
+            note("e", "2024-01-01T00:05:00Z", Some("missing")),
+        ];
+
+        let threads = build_threads(&items);
+
+        assert_eq!(1, threads.conversations.len());
+        assert!(threads.cycles.is_empty());
+
+        let conversation = &threads.conversations[0];
+
+        assert_eq!(Some("https://x.y/contexts/1"), conversation.id);
+        assert_eq!(2, conversation.roots.len());
+
+        let root = &conversation.roots[0];
+
+        assert_eq!(4, root.node.size());
+        assert!(root.missing_parent.is_none());
+
+        let order: Vec<&str> = root.node.flatten()
+            .into_iter()
+            .map(|item| item.content.as_deref().unwrap())
+            .collect();
+
+        assert_eq!(vec!["a", "b", "d", "c"], order);
+
+        assert_eq!(
+            "https://x.y/notes/missing",
+            conversation.roots[1].missing_parent.unwrap().as_str()
+        );
+
+        assert_eq!(1, threads.missing_parents.len());
+    }
+
+    #[test]
+    fn test_reply_cycle_is_broken() {
+        let items = vec![
+            note("a", "2024-01-01T00:01:00Z", Some("b")),
+            note("b", "2024-01-01T00:02:00Z", Some("a")),
+        ];
+
+        let threads = build_threads(&items);
+
+        assert_eq!(1, threads.cycles.len());
+
+        let root = &threads.conversations[0].roots[0];
+
+        assert!(root.breaks_cycle);
+        assert_eq!("https://x.y/notes/a", root.node.content.object_id_str());
+        assert_eq!(2, root.node.size());
+    }
+
+    #[test]
+    fn test_deep_thread_is_built() {
+        let depth: usize = 20_000;
+
+        let items: Vec<Content> = (0..depth)
+            .map(|index| note(
+                &index.to_string(),
+                "2024-01-01T00:00:00Z",
+                index.checked_sub(1).map(|parent| parent.to_string()).as_deref(),
+            ))
+            .collect();
+
+        let threads = build_threads(&items);
+        let root = &threads.conversations[0].roots[0];
+
+        assert_eq!(depth, root.node.size());
+        assert_eq!(depth, root.node.flatten().len());
+        assert_eq!("https://x.y/notes/0", root.node.content.object_id_str());
+
+        let cloned = root.node.clone();
+
+        assert_eq!(depth, cloned.size());
+        assert!(format!("{cloned:?}").contains(r#"(19999, "https://x.y/notes/19999")"#));
+
+        let (content, replies) = cloned.into_parts();
+
+        assert_eq!("https://x.y/notes/0", content.object_id_str());
+        assert_eq!(1, replies.len());
+    }
+}
@@ -0,0 +1,573 @@
+use std::fmt;
+
+use crate::activity::Activity;
+use crate::actor::{Actor, CompoundActorReference};
+use crate::attachment::AttachmentKind;
+use crate::content::Content;
+use crate::entity::{EntityType, is_activity_type};
+use crate::image::ImageReference;
+use crate::object::ObjectTrait;
+
+/// View of threadiverse post, e.g. Lemmy, Kbin or PieFed `Page`.
+#[derive(Debug, Clone)]
+pub struct ThreadiversePost<'a> {
+    /// Post itself.
+    pub content: &'a Content,
+}
+
+impl<'a> ThreadiversePost<'a> {
+    /// Returns title of post.
+    pub fn title(&self) -> Option<&'a str> {
+        self.content.object_entity.name.as_deref()
+    }
+
+    /// Returns ID of community post belongs to.
+    pub fn community(&self) -> Option<&'a url::Url> {
+        self.content.audience.as_ref()
+            .and_then(|audience| audience.as_id_vec().into_iter().next())
+    }
+
+    /// Returns ID of author, community is never returned.
+    pub fn author(&self) -> Option<&'a url::Url> {
+        self.content.author_id()
+    }
+
+    /// Returns URL post links to, if it is link post.
+    pub fn link(&self) -> Option<&'a url::Url> {
+        self.content.attachment.iter()
+            .flat_map(|reference| reference.as_vec())
+            .filter(|attachment| attachment.kind() == AttachmentKind::Link)
+            .find_map(|attachment| attachment.url.as_ref())
+    }
+
+    /// Returns thumbnail or image of post.
+    pub fn image(&self) -> Option<&'a url::Url> {
+        match self.content.icon.as_ref()? {
+            ImageReference::Url(url) => Some(url),
+            ImageReference::Single(image) => image.url.as_ref(),
+            ImageReference::List(images) => images.iter()
+                .find_map(|image| image.url.as_ref()),
+        }
+    }
+
+    /// Returns `true` if post does not accept comments.
+    pub fn is_locked(&self) -> bool {
+        self.content.comments_enabled == Some(false)
+    }
+
+    /// Returns `true` if post is pinned in community.
+    pub fn is_stickied(&self) -> bool {
+        self.content.stickied.unwrap_or(false)
+    }
+}
+
+impl Content {
+    /// Returns ID of author, actors in `audience` are not authors,
+    /// see [crate::actor::CompoundActorReference::author_id()].
+    pub fn author_id(&self) -> Option<&url::Url> {
+        self.attributed_to.author_id(self.audience.as_ref())
+    }
+
+    /// Returns threadiverse post view if this content is `Page`
+    /// or is posted to community.
+    pub fn threadiverse_post(&self) -> Option<ThreadiversePost<'_>> {
+        let is_post = self.entity_type() == EntityType::Page ||
+            self.audience.is_some();
+
+        match is_post {
+            true => Some(ThreadiversePost { content: self }),
+            false => None,
+        }
+    }
+}
+
+/// View of threadiverse community, that is `Group` actor.
+#[derive(Debug, Clone)]
+pub struct Community<'a> {
+    /// Community actor.
+    pub actor: &'a Actor,
+}
+
+impl<'a> Community<'a> {
+    /// Returns link to collection of moderators.
+    pub fn moderators(&self) -> Option<&'a url::Url> {
+        self.actor.moderators.as_ref()
+    }
+
+    /// Returns `true` if only moderators could post to community.
+    pub fn is_posting_restricted_to_mods(&self) -> bool {
+        self.actor.posting_restricted_to_mods.unwrap_or(false)
+    }
+}
+
+impl Actor {
+    /// Returns community view if this actor is `Group`.
+    pub fn community(&self) -> Option<Community<'_>> {
+        match self.entity_type() {
+            EntityType::Group => Some(Community { actor: self }),
+            _ => None,
+        }
+    }
+}
+
+/// Direction of vote.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum VoteDirection {
+    /// `Like` activity.
+    Up,
+
+    /// `Dislike` activity.
+    Down,
+}
+
+/// Vote on threadiverse post or comment.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Vote {
+    /// Who votes.
+    pub voter: url::Url,
+
+    /// Object voted on.
+    pub object: url::Url,
+
+    /// Up- or downvote.
+    pub direction: VoteDirection,
+
+    /// Community vote is cast in, if reported.
+    pub audience: Option<url::Url>,
+
+    /// `true` if vote is retracted with `Undo`.
+    pub retracted: bool,
+}
+
+impl Vote {
+    /// Converts `Like`, `Dislike` or `Undo` of those activity to vote.
+    /// Undone vote must be cast by actor of `Undo`.
+    pub fn from_activity(activity: &Activity) -> Option<Self> {
+        if activity.entity_type() == EntityType::Undo {
+            let inner: Activity = serde_json::from_value(activity.object.clone()).ok()?;
+            let vote = Self::from_activity(&inner)?;
+
+            if vote.retracted || Some(&vote.voter) != activity.actor.id() {
+                return None;
+            }
+
+            return Some(Self {
+                retracted: true,
+                audience: vote.audience.or_else(|| Self::audience_of(activity)),
+                ..vote
+            });
+        }
+
+        let direction = match activity.entity_type() {
+            EntityType::Like => VoteDirection::Up,
+            EntityType::Dislike => VoteDirection::Down,
+            _ => return None,
+        };
+
+        Some(Self {
+            voter: activity.actor.id()?.clone(),
+            object: activity.inner_object_id()?,
+            direction,
+            audience: Self::audience_of(activity),
+            retracted: false,
+        })
+    }
+
+    fn audience_of(activity: &Activity) -> Option<url::Url> {
+        activity.audience.as_ref()
+            .and_then(|audience| audience.as_id_vec().into_iter().next())
+            .cloned()
+    }
+}
+
+/// Reasons to reject `Announce` as FEP-1b12 group announce.
+/// See: <https://codeberg.org/fediverse/fep/src/branch/main/fep/1b12/fep-1b12.md>
+#[derive(Debug)]
+pub enum GroupAnnounceError {
+    /// Activity is not `Announce`.
+    NotAnnounce(EntityType),
+
+    /// Announced activity is referred by URL only, it has to be fetched.
+    NotEmbedded,
+
+    /// Announced object is not an activity, e.g. it is boosted `Note`.
+    NotActivity(EntityType),
+
+    /// Announced activity could not be parsed.
+    InvalidActivity(serde_json::Error),
+
+    /// Announcing actor is not a Group.
+    NotGroup,
+
+    /// Announced activity has no actor.
+    MissingActor,
+
+    /// Announced activity or its object is not from the same origin
+    /// as its actor.
+    OriginMismatch {
+        /// ID of activity or object.
+        id: String,
+        /// ID of actor.
+        actor: String,
+    },
+}
+
+impl fmt::Display for GroupAnnounceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GroupAnnounceError::NotAnnounce(entity_type) => write!(
+                f, "activity is {entity_type}, not Announce"
+            ),
+
+            GroupAnnounceError::NotEmbedded => f.write_str(
+                "announced activity is not embedded"
+            ),
+
+            GroupAnnounceError::NotActivity(entity_type) => write!(
+                f, "announced object is {entity_type}, not activity"
+            ),
+
+            GroupAnnounceError::InvalidActivity(err) => write!(
+                f, "announced activity is not valid: {err}"
+            ),
+
+            GroupAnnounceError::NotGroup => f.write_str(
+                "announcing actor is not a group"
+            ),
+
+            GroupAnnounceError::MissingActor => f.write_str(
+                "announced activity has no actor"
+            ),
+
+            GroupAnnounceError::OriginMismatch { id, actor } => write!(
+                f, "{id} is not from the same origin as actor {actor}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GroupAnnounceError {}
+
+/// Activity of community member redistributed by Group actor,
+/// e.g. `Announce(Create(Page))` or `Announce(Like)`.
+#[derive(Debug)]
+pub struct GroupAnnounce {
+    /// ID of Group announcing activity.
+    pub group: url::Url,
+
+    /// Actor of original activity.
+    pub actor: url::Url,
+
+    /// Original activity.
+    pub activity: Activity,
+}
+
+impl GroupAnnounce {
+    /// Unwraps original activity from `announce` made by Group actor.
+    ///
+    /// Announcer is accepted as Group if it is typed as `Group`, or if it is
+    /// `audience` or addressee of announced activity. `audience` of
+    /// `announce` itself is not considered, as it is asserted by announcer.
+    ///
+    /// Announced activity is not signed by its actor, so it is only checked
+    /// that the activity and its embedded object, if any, claim the same
+    /// origin as actor, which rejects obvious forgeries but does not prove
+    /// authenticity. Unwrapped activity is to be trusted only if
+    /// [GroupAnnounce::needs_refetch()] is `false`, otherwise it has to be
+    /// fetched by its ID from origin. Authenticity of `announce` itself
+    /// is up to the caller.
+    pub fn from_activity(announce: &Activity) -> Result<Self, GroupAnnounceError> {
+        if announce.entity_type() != EntityType::Announce {
+            return Err(GroupAnnounceError::NotAnnounce(announce.entity_type()));
+        }
+
+        if !announce.object.is_object() {
+            return Err(GroupAnnounceError::NotEmbedded);
+        }
+
+        let object_type = announce.object.get("type")
+            .and_then(|value| serde_json::from_value::<EntityType>(value.clone()).ok())
+            .unwrap_or(EntityType::Unknown);
+
+        if !is_activity_type(object_type) {
+            return Err(GroupAnnounceError::NotActivity(object_type));
+        }
+
+        let activity: Activity = serde_json::from_value(announce.object.clone())
+            .map_err(GroupAnnounceError::InvalidActivity)?;
+
+        let group = announce.actor.id()
+            .cloned()
+            .ok_or(GroupAnnounceError::NotGroup)?;
+
+        let typed_as_group = match &announce.actor {
+            CompoundActorReference::Reference(reference) =>
+                reference.entity_type() == Some(EntityType::Group),
+            CompoundActorReference::List(_) => false,
+        };
+
+        let is_audience = activity.audience.as_ref()
+            .map(|audience| audience.as_id_vec().contains(&&group))
+            .unwrap_or(false);
+
+        let is_addressee = activity.object_entity.to.as_ref()
+            .map(|to| to.as_id_vec().contains(&&group))
+            .unwrap_or(false);
+
+        if !(typed_as_group || is_audience || is_addressee) {
+            return Err(GroupAnnounceError::NotGroup);
+        }
+
+        let actor = activity.actor.id()
+            .cloned()
+            .ok_or(GroupAnnounceError::MissingActor)?;
+
+        check_origin(activity.object_id(), &actor)?;
+
+        let embedded_object_id = activity.object.get("id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| url::Url::parse(id).ok());
+
+        // Like and Undo embed objects of other actors, only created
+        // and updated objects must be authored by actor.
+        let owns_object = matches!(
+            activity.entity_type(),
+            EntityType::Create | EntityType::Update
+        );
+
+        if let (true, Some(object_id)) = (owns_object, embedded_object_id) {
+            check_origin(&object_id, &actor)?;
+        }
+
+        Ok(Self {
+            group,
+            actor,
+            activity,
+        })
+    }
+
+    /// Returns `true` if unwrapped activity has to be fetched by
+    /// [Activity::activity_id()] from its origin before it is trusted,
+    /// that is unless Group and actor share an origin, so authentic
+    /// announce vouches for the activity.
+    pub fn needs_refetch(&self) -> bool {
+        self.group.origin() != self.actor.origin()
+    }
+}
+
+fn check_origin(id: &url::Url, actor: &url::Url) -> Result<(), GroupAnnounceError> {
+    match id.origin() == actor.origin() {
+        true => Ok(()),
+
+        false => Err(GroupAnnounceError::OriginMismatch {
+            id: id.to_string(),
+            actor: actor.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::activity::Activity;
+    use crate::actor::Actor;
+    use crate::content::Content;
+    use crate::entity::EntityType;
+    use crate::object::ObjectTrait;
+    use crate::threadiverse::{GroupAnnounce, GroupAnnounceError, Vote, VoteDirection};
+
+    #[test]
+    fn test_lemmy_page() {
+        let content: Content = serde_json::from_str(r#"{
+          "type": "Page",
+          "id": "https://lemmy.z.y.x/post/1",
+          "attributedTo": [
+            {"type": "Group", "id": "https://lemmy.z.y.x/c/cats"},
+            {"type": "Person", "id": "https://lemmy.z.y.x/u/xyz"}
+          ],
+          "to": ["https://lemmy.z.y.x/c/cats", "https://www.w3.org/ns/activitystreams#Public"],
+          "audience": "https://lemmy.z.y.x/c/cats",
+          "name": "Look at this box",
+          "content": "<p>Very nice</p>",
+          "attachment": [{"type": "Link", "href": "https://boxes.z.y.x/1"}],
+          "image": {"type": "Image", "url": "https://lemmy.z.y.x/pictrs/image/1.jpg"},
+          "commentsEnabled": false,
+          "stickied": true,
+          "sensitive": false,
+          "published": "2024-01-01T00:00:00Z"
+        }"#).unwrap();
+
+        let post = content.threadiverse_post().unwrap();
+
+        assert_eq!(Some("Look at this box"), post.title());
+        assert_eq!("https://lemmy.z.y.x/c/cats", post.community().unwrap().as_str());
+        assert_eq!("https://lemmy.z.y.x/u/xyz", post.author().unwrap().as_str());
+        assert_eq!("https://boxes.z.y.x/1", post.link().unwrap().as_str());
+        assert_eq!("https://lemmy.z.y.x/pictrs/image/1.jpg", post.image().unwrap().as_str());
+        assert!(post.is_locked());
+        assert!(post.is_stickied());
+
+        let community: Actor = serde_json::from_str(r#"{
+          "type": "Group",
+          "id": "https://lemmy.z.y.x/c/cats",
+          "preferredUsername": "cats",
+          "inbox": "https://lemmy.z.y.x/c/cats/inbox",
+          "outbox": "https://lemmy.z.y.x/c/cats/outbox",
+          "moderators": "https://lemmy.z.y.x/c/cats/moderators",
+          "postingRestrictedToMods": true
+        }"#).unwrap();
+
+        let community = community.community().unwrap();
+
+        assert_eq!("https://lemmy.z.y.x/c/cats/moderators", community.moderators().unwrap().as_str());
+        assert!(community.is_posting_restricted_to_mods());
+    }
+
+    #[test]
+    fn test_malformed_post_properties_are_ignored() {
+        let content: Content = serde_json::from_str(r#"{
+          "type": "Page",
+          "id": "https://lemmy.z.y.x/post/1",
+          "attributedTo": "https://lemmy.z.y.x/u/xyz",
+          "audience": 1,
+          "name": "Look at this box",
+          "commentsEnabled": "true",
+          "stickied": "yes",
+          "published": "2024-01-01T00:00:00Z"
+        }"#).unwrap();
+
+        let post = content.threadiverse_post().unwrap();
+
+        assert!(post.community().is_none());
+        assert!(!post.is_locked());
+        assert!(!post.is_stickied());
+    }
+
+    #[test]
+    fn test_votes() {
+        let activity: Activity = serde_json::from_str(r#"{
+          "type": "Undo",
+          "id": "https://lemmy.z.y.x/activities/undo/1",
+          "actor": "https://lemmy.z.y.x/u/xyz",
+          "object": {
+            "type": "Dislike",
+            "id": "https://lemmy.z.y.x/activities/dislike/1",
+            "actor": "https://lemmy.z.y.x/u/xyz",
+            "object": "https://lemmy.z.y.x/post/1",
+            "audience": "https://lemmy.z.y.x/c/cats"
+          }
+        }"#).unwrap();
+
+        let vote = Vote::from_activity(&activity).unwrap();
+
+        assert_eq!(VoteDirection::Down, vote.direction);
+        assert_eq!("https://lemmy.z.y.x/post/1", vote.object.as_str());
+        assert_eq!("https://lemmy.z.y.x/c/cats", vote.audience.unwrap().as_str());
+        assert!(vote.retracted);
+
+        let mut activity = activity;
+        activity.actor = serde_json::from_str(r#""https://lemmy.z.y.x/u/abc""#).unwrap();
+
+        assert!(Vote::from_activity(&activity).is_none());
+    }
+
+    #[test]
+    fn test_group_announce() {
+        let announce = |object: serde_json::Value| -> Activity {
+            serde_json::from_value(serde_json::json!({
+                "type": "Announce",
+                "id": "https://lemmy.z.y.x/activities/announce/1",
+                "actor": "https://lemmy.z.y.x/c/cats",
+                "to": ["https://www.w3.org/ns/activitystreams#Public"],
+                "object": object
+            })).unwrap()
+        };
+
+        let create = serde_json::json!({
+            "type": "Create",
+            "id": "https://kbin.a.b/activities/create/1",
+            "actor": "https://kbin.a.b/u/xyz",
+            "audience": "https://lemmy.z.y.x/c/cats",
+            "to": ["https://lemmy.z.y.x/c/cats"],
+            "object": {
+                "type": "Page",
+                "id": "https://kbin.a.b/m/cats/t/1",
+                "attributedTo": "https://kbin.a.b/u/xyz",
+                "name": "Box",
+                "published": "2024-01-01T00:00:00Z"
+            }
+        });
+
+        let unwrapped = GroupAnnounce::from_activity(&announce(create.clone())).unwrap();
+
+        assert_eq!("https://lemmy.z.y.x/c/cats", unwrapped.group.as_str());
+        assert_eq!("https://kbin.a.b/u/xyz", unwrapped.actor.as_str());
+        assert_eq!(EntityType::Create, unwrapped.activity.entity_type());
+        assert_eq!(EntityType::Page, unwrapped.activity.inner_object_type());
+        assert!(unwrapped.needs_refetch());
+
+        let mut forged = create;
+        forged["object"]["id"] = serde_json::json!("https://elsewhere.c.d/post/1");
+
+        assert!(matches!(
+            GroupAnnounce::from_activity(&announce(forged)),
+            Err(GroupAnnounceError::OriginMismatch { .. })
+        ));
+
+        let boost = serde_json::json!({
+            "type": "Note",
+            "id": "https://kbin.a.b/m/cats/t/1",
+            "attributedTo": "https://kbin.a.b/u/xyz"
+        });
+
+        assert!(matches!(
+            GroupAnnounce::from_activity(&announce(boost)),
+            Err(GroupAnnounceError::NotActivity(EntityType::Note))
+        ));
+    }
+
+    #[test]
+    fn test_self_asserted_group_announce_is_rejected() {
+        let announce: Activity = serde_json::from_value(serde_json::json!({
+            "type": "Announce",
+            "id": "https://z.y.x/users/xyz/announces/1",
+            "actor": "https://z.y.x/users/xyz",
+            "audience": "https://z.y.x/users/xyz",
+            "object": {
+                "type": "Create",
+                "id": "https://z.y.x/users/abc/activities/1",
+                "actor": "https://z.y.x/users/abc",
+                "object": {
+                    "type": "Note",
+                    "id": "https://z.y.x/users/abc/statuses/1",
+                    "attributedTo": "https://z.y.x/users/abc",
+                    "published": "2024-01-01T00:00:00Z"
+                }
+            }
+        })).unwrap();
+
+        assert!(matches!(
+            GroupAnnounce::from_activity(&announce),
+            Err(GroupAnnounceError::NotGroup)
+        ));
+    }
+
+    #[test]
+    fn test_group_announce_from_same_origin_needs_no_refetch() {
+        let announce: Activity = serde_json::from_value(serde_json::json!({
+            "type": "Announce",
+            "id": "https://lemmy.z.y.x/activities/announce/2",
+            "actor": "https://lemmy.z.y.x/c/cats",
+            "object": {
+                "type": "Like",
+                "id": "https://lemmy.z.y.x/activities/like/1",
+                "actor": "https://lemmy.z.y.x/u/xyz",
+                "object": "https://kbin.a.b/m/cats/t/1",
+                "audience": "https://lemmy.z.y.x/c/cats"
+            }
+        })).unwrap();
+
+        let unwrapped = GroupAnnounce::from_activity(&announce).unwrap();
+
+        assert!(!unwrapped.needs_refetch());
+    }
+}
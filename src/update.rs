@@ -0,0 +1,384 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::activity::Activity;
+use crate::content::Content;
+use crate::entity::EntityType;
+use crate::object::ObjectTrait;
+
+/// Properties holding text that is usually indexed.
+const TEXT_PROPERTIES: [&str; 8] = [
+    "name",
+    "nameMap",
+    "summary",
+    "summaryMap",
+    "content",
+    "contentMap",
+    "source",
+    "_misskey_content",
+];
+
+/// Reasons to reject Update of stored content.
+#[derive(Debug)]
+pub enum UpdateError {
+    /// Activity is not `Update`.
+    NotUpdate(EntityType),
+
+    /// Update refers object by URL only, there is nothing to apply.
+    NotEmbedded,
+
+    /// Updated object could not be parsed as [Content].
+    InvalidObject(serde_json::Error),
+
+    /// Update is for some other object.
+    IdMismatch,
+
+    /// Update is made by actor other than author of stored content.
+    AuthorMismatch,
+
+    /// Updated object has no `updated` timestamp, so it is not possible
+    /// to tell if it is newer than stored one.
+    MissingTimestamp,
+
+    /// Updated object is not newer than stored one.
+    Stale {
+        /// When stored content was last updated or published.
+        stored: DateTime<Utc>,
+        /// When updated content was updated.
+        update: DateTime<Utc>,
+    },
+
+    /// Stored content or update is not a poll.
+    NotPoll,
+
+    /// Poll options are changed, that is poll is edited rather than
+    /// its results are updated.
+    PollOptionsChanged,
+
+    /// Update has fewer votes than stored poll.
+    OutdatedPollResults,
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UpdateError::NotUpdate(entity_type) => write!(
+                f, "activity is {entity_type}, not Update"
+            ),
+
+            UpdateError::NotEmbedded => f.write_str(
+                "updated object is not embedded into activity"
+            ),
+
+            UpdateError::InvalidObject(err) => write!(
+                f, "updated object is not valid content: {err}"
+            ),
+
+            UpdateError::IdMismatch => f.write_str(
+                "updated object ID does not match stored content ID"
+            ),
+
+            UpdateError::AuthorMismatch => f.write_str(
+                "update is not made by author of stored content"
+            ),
+
+            UpdateError::MissingTimestamp => f.write_str(
+                "updated object has no 'updated' timestamp"
+            ),
+
+            UpdateError::Stale { stored, update } => write!(
+                f, "update from {update} is not newer than stored {stored}"
+            ),
+
+            UpdateError::NotPoll => f.write_str(
+                "stored content or update is not a poll"
+            ),
+
+            UpdateError::PollOptionsChanged => f.write_str(
+                "poll options are changed"
+            ),
+
+            UpdateError::OutdatedPollResults => f.write_str(
+                "update has fewer votes than stored poll"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+/// Change of single top level property of content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyChange {
+    /// Name of property as in serialized form, e.g. `contentMap`.
+    pub property: String,
+
+    /// Value before change, `None` if property was not set.
+    pub old_value: Option<Value>,
+
+    /// Value after change, `None` if property is removed.
+    pub new_value: Option<Value>,
+}
+
+/// Property level difference between two versions of content.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentDiff {
+    /// Changed properties ordered by name.
+    pub changes: Vec<PropertyChange>,
+}
+
+impl ContentDiff {
+    /// Computes difference between `old` and `new` versions of content.
+    /// Properties are compared in their serialized form, so properties
+    /// not modelled by this crate are compared as well.
+    pub fn between(old: &Content, new: &Content) -> Self {
+        let old_value = serde_json::to_value(old).ok();
+        let new_value = serde_json::to_value(new).ok();
+
+        let old_map = old_value.as_ref().and_then(|value| value.as_object());
+        let new_map = new_value.as_ref().and_then(|value| value.as_object());
+
+        let mut properties: Vec<&String> = old_map.iter()
+            .chain(new_map.iter())
+            .flat_map(|map| map.keys())
+            .collect();
+
+        properties.sort();
+        properties.dedup();
+
+        let changes = properties.into_iter()
+            .filter_map(|property| {
+                let old_value = old_map.and_then(|map| map.get(property));
+                let new_value = new_map.and_then(|map| map.get(property));
+
+                if old_value == new_value {
+                    return None;
+                }
+
+                Some(PropertyChange {
+                    property: property.clone(),
+                    old_value: old_value.cloned(),
+                    new_value: new_value.cloned(),
+                })
+            })
+            .collect();
+
+        Self {
+            changes,
+        }
+    }
+
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns `true` if `property` is changed.
+    pub fn is_changed(&self, property: &str) -> bool {
+        self.changes.iter()
+            .any(|change| change.property == property)
+    }
+
+    /// Returns `true` if any property holding indexed text is changed,
+    /// e.g. `content` or `summary`. Changes in counters, timestamps and
+    /// similar properties do not require content to be re-indexed.
+    pub fn is_text_changed(&self) -> bool {
+        TEXT_PROPERTIES.iter()
+            .any(|property| self.is_changed(property))
+    }
+}
+
+impl Content {
+    /// Returns point of time content was last updated, or published
+    /// if it was never updated.
+    pub fn last_modified(&self) -> DateTime<Utc> {
+        self.object_entity.updated
+            .unwrap_or(self.published)
+    }
+
+    /// Replaces this content with `update` and returns what is changed.
+    ///
+    /// Update is rejected if it is for other object or author,
+    /// or if it is not newer than this content. Update must have
+    /// `updated` timestamp.
+    pub fn apply_update(&mut self, update: Content) -> Result<ContentDiff, UpdateError> {
+        if update.object_id() != self.object_id() {
+            return Err(UpdateError::IdMismatch);
+        }
+
+        if update.author_id() != self.author_id() {
+            return Err(UpdateError::AuthorMismatch);
+        }
+
+        let update_time = match update.object_entity.updated {
+            None => return Err(UpdateError::MissingTimestamp),
+            Some(updated) => updated,
+        };
+
+        let stored_time = self.last_modified();
+
+        if update_time <= stored_time {
+            return Err(UpdateError::Stale {
+                stored: stored_time,
+                update: update_time,
+            });
+        }
+
+        let diff = ContentDiff::between(self, &update);
+        *self = update;
+
+        Ok(diff)
+    }
+
+    /// Applies object embedded into `Update` activity to this content,
+    /// see [Content::apply_update()]. Actor of activity must be author
+    /// of this content.
+    pub fn apply_update_activity(
+        &mut self,
+        activity: &Activity,
+    ) -> Result<ContentDiff, UpdateError> {
+        let update = self.parse_update_activity(activity)?;
+        self.apply_update(update)
+    }
+
+    /// Extracts updated content from `Update` activity after checking
+    /// activity is made by author of this content.
+    pub(crate) fn parse_update_activity(
+        &self,
+        activity: &Activity,
+    ) -> Result<Content, UpdateError> {
+        if activity.entity_type() != EntityType::Update {
+            return Err(UpdateError::NotUpdate(activity.entity_type()));
+        }
+
+        if !activity.object.is_object() {
+            return Err(UpdateError::NotEmbedded);
+        }
+
+        if activity.actor.id() != self.author_id() {
+            return Err(UpdateError::AuthorMismatch);
+        }
+
+        serde_json::from_value(activity.object.clone())
+            .map_err(UpdateError::InvalidObject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::activity::Activity;
+    use crate::content::Content;
+    use crate::entity::EntityType;
+    use crate::update::{ContentDiff, UpdateError};
+
+    fn note(content: &str, updated: Option<&str>) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "id": "https://x.y/notes/1",
+            "type": "Note",
+            "attributedTo": "https://x.y/users/xyz",
+            "content": content,
+            "published": "2024-01-01T00:00:00Z",
+        });
+
+        if let Some(updated) = updated {
+            value["updated"] = updated.into();
+        }
+
+        value
+    }
+
+    #[test]
+    fn test_update_is_applied() {
+        let mut stored: Content = serde_json::from_value(note("old", None)).unwrap();
+
+        let update: Activity = serde_json::from_value(serde_json::json!({
+            "id": "https://x.y/notes/1#updates/1",
+            "type": "Update",
+            "actor": "https://x.y/users/xyz",
+            "object": note("new", Some("2024-01-02T00:00:00Z")),
+        })).unwrap();
+
+        let diff = stored.apply_update_activity(&update).unwrap();
+
+        assert!(diff.is_text_changed());
+        assert!(diff.is_changed("updated"));
+        assert_eq!(2, diff.changes.len());
+        assert_eq!(Some("new"), stored.content.as_deref());
+    }
+
+    #[test]
+    fn test_localized_summary_change_is_text_change() {
+        let stored: Content = serde_json::from_value(note("text", None)).unwrap();
+
+        let mut value = note("text", Some("2024-01-02T00:00:00Z"));
+        value["summaryMap"] = serde_json::json!({"en": "Cats"});
+
+        let updated: Content = serde_json::from_value(value).unwrap();
+        let diff = ContentDiff::between(&stored, &updated);
+
+        assert!(diff.is_changed("summaryMap"));
+        assert!(diff.is_text_changed());
+    }
+
+    #[test]
+    fn test_update_of_community_post_is_applied() {
+        let post = |name: &str, updated: Option<&str>| {
+            let mut value = serde_json::json!({
+                "id": "https://lemmy.z.y.x/post/1",
+                "type": "Page",
+                "attributedTo": ["https://lemmy.z.y.x/c/cats", "https://lemmy.z.y.x/u/xyz"],
+                "audience": "https://lemmy.z.y.x/c/cats",
+                "name": name,
+                "published": "2024-01-01T00:00:00Z",
+            });
+
+            if let Some(updated) = updated {
+                value["updated"] = updated.into();
+            }
+
+            value
+        };
+
+        let mut stored: Content = serde_json::from_value(post("Box", None)).unwrap();
+
+        let update: Activity = serde_json::from_value(serde_json::json!({
+            "id": "https://lemmy.z.y.x/activities/update/1",
+            "type": "Update",
+            "actor": "https://lemmy.z.y.x/u/xyz",
+            "object": post("Boxes", Some("2024-01-02T00:00:00Z")),
+        })).unwrap();
+
+        assert_eq!(EntityType::Page, update.inner_object_type());
+
+        stored.apply_update_activity(&update).unwrap();
+
+        assert_eq!(Some("Boxes"), stored.object_entity.name.as_deref());
+    }
+
+    #[test]
+    fn test_stale_update_is_rejected() {
+        let mut stored: Content = serde_json::from_value(
+            note("new", Some("2024-01-03T00:00:00Z"))
+        ).unwrap();
+
+        let update: Content = serde_json::from_value(
+            note("old", Some("2024-01-02T00:00:00Z"))
+        ).unwrap();
+
+        assert!(matches!(
+            stored.apply_update(update),
+            Err(UpdateError::Stale { .. })
+        ));
+
+        let update: Content = serde_json::from_value(note("old", None)).unwrap();
+
+        assert!(matches!(
+            stored.apply_update(update),
+            Err(UpdateError::MissingTimestamp)
+        ));
+
+        assert_eq!(Some("new"), stored.content.as_deref());
+    }
+}
@@ -0,0 +1,673 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::content::Content;
+use crate::duration::DurationValue;
+use crate::entity::{EntityType, UnknownProperties};
+use crate::object::{Link, ObjectTrait, UrlReference};
+
+/// Media type of HLS streaming playlist.
+pub const HLS_MEDIA_TYPE: &str = "application/x-mpegURL";
+
+/// PeerTube's identifier and human-readable name pair,
+/// used for category, licence and language of video.
+///
+/// Example:
+/// ```json
+/// "category": {
+///   "identifier": "15",
+///   "name": "Science & Technology"
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VideoLabel {
+    /// Identifier, e.g. numeric category or language code.
+    pub identifier: String,
+
+    /// Human-readable name in instance language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Subtitles of video.
+///
+/// Example:
+/// ```json
+/// {
+///   "identifier": "en",
+///   "name": "English",
+///   "url": "https://peertube.stream/lazy-static/video-captions/xxx-en.vtt"
+/// }
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Subtitle {
+    /// Language code of subtitles.
+    pub identifier: String,
+
+    /// Human-readable language name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Link to subtitles file, recent PeerTube versions send
+    /// list of links to WebVTT file and HLS playlist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<UrlReference>,
+
+    /// Properties not modelled by this crate.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+/// Image of video preview.
+/// Storyboards additionally define tile geometry.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PreviewImage {
+    /// Link to image.
+    pub href: url::Url,
+
+    /// Media type of image, e.g. `image/jpeg`.
+    #[serde(rename = "mediaType", alias = "media_type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+
+    /// Width of image in pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+
+    /// Height of image in pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+
+    /// Width of storyboard tile in pixels.
+    #[serde(rename = "tileWidth")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_width: Option<u32>,
+
+    /// Height of storyboard tile in pixels.
+    #[serde(rename = "tileHeight")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_height: Option<u32>,
+
+    /// Part of video each storyboard tile represents.
+    #[serde(rename = "tileDuration")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile_duration: Option<DurationValue>,
+
+    /// Properties not modelled by this crate.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+/// Geometry of storyboard, that is a grid of video frames
+/// stitched into single image.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StoryboardGeometry {
+    /// Number of tiles in row.
+    pub columns: u32,
+
+    /// Number of tile rows.
+    pub rows: u32,
+
+    /// Width of tile in pixels.
+    pub tile_width: u32,
+
+    /// Height of tile in pixels.
+    pub tile_height: u32,
+
+    /// Part of video each tile represents.
+    pub tile_duration: Duration,
+}
+
+/// Rectangle within storyboard image.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Tile {
+    /// Offset of tile from the left edge of image in pixels.
+    pub x: u32,
+
+    /// Offset of tile from the top edge of image in pixels.
+    pub y: u32,
+
+    /// Width of tile in pixels.
+    pub width: u32,
+
+    /// Height of tile in pixels.
+    pub height: u32,
+}
+
+impl StoryboardGeometry {
+    /// Returns total number of tiles, saturating at [u32::MAX].
+    pub fn tiles(&self) -> u32 {
+        self.columns.saturating_mul(self.rows)
+    }
+
+    /// Returns tile showing frame at `position` of video.
+    /// Positions past the last tile map to the last tile.
+    /// `None` is returned if geometry has no tiles.
+    pub fn tile_at(&self, position: Duration) -> Option<Tile> {
+        if self.columns == 0 || self.rows == 0 {
+            return None;
+        }
+
+        // float to integer conversion saturates
+        let index = match self.tile_duration.is_zero() {
+            true => 0,
+            false => (position.as_secs_f64() / self.tile_duration.as_secs_f64()) as u32,
+        };
+
+        let index = index.min(self.tiles() - 1);
+
+        Some(Tile {
+            x: (index % self.columns).saturating_mul(self.tile_width),
+            y: (index / self.columns).saturating_mul(self.tile_height),
+            width: self.tile_width,
+            height: self.tile_height,
+        })
+    }
+}
+
+impl PreviewImage {
+    /// Returns storyboard geometry if image is tiled storyboard.
+    pub fn storyboard_geometry(&self) -> Option<StoryboardGeometry> {
+        let tile_width = self.tile_width.filter(|width| *width > 0)?;
+        let tile_height = self.tile_height.filter(|height| *height > 0)?;
+
+        let columns = self.width? / tile_width;
+        let rows = self.height? / tile_height;
+
+        if columns == 0 || rows == 0 {
+            return None;
+        }
+
+        Some(StoryboardGeometry {
+            columns,
+            rows,
+            tile_width,
+            tile_height,
+            tile_duration: self.tile_duration.as_ref()
+                .and_then(|duration| duration.to_duration())?,
+        })
+    }
+}
+
+/// Kind of video preview.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PreviewKind {
+    /// Single image, e.g. thumbnail.
+    Static,
+
+    /// Tiled image of video frames, e.g. to show on seek bar.
+    Storyboard,
+}
+
+/// Video preview.
+///
+/// Example:
+/// ```json
+/// "preview": [
+///   {
+///     "type": "Image",
+///     "rel": ["storyboard"],
+///     "url": [
+///       {
+///         "mediaType": "image/jpeg",
+///         "href": "https://peertube.stream/lazy-static/storyboards/xyz.jpg",
+///         "width": 1920,
+///         "height": 1080,
+///         "tileWidth": 192,
+///         "tileHeight": 108,
+///         "tileDuration": "PT1S"
+///       }
+///     ]
+///   }
+/// ]
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct VideoPreview {
+    /// Type of preview, usually `Image`.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_type: Option<EntityType>,
+
+    /// Relation of preview to video, e.g. `storyboard`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rel: Option<Vec<String>>,
+
+    /// Preview images.
+    #[serde(default)]
+    pub url: Vec<PreviewImage>,
+
+    /// Properties not modelled by this crate.
+    #[serde(flatten)]
+    pub unknown_properties: UnknownProperties,
+}
+
+impl VideoPreview {
+    /// Returns kind of preview, tiled images are storyboards
+    /// even if `rel` is not set.
+    pub fn kind(&self) -> PreviewKind {
+        let is_storyboard = self.rel.iter()
+            .flatten()
+            .any(|rel| rel == "storyboard") ||
+            self.url.iter()
+                .any(|image| image.storyboard_geometry().is_some());
+
+        match is_storyboard {
+            true => PreviewKind::Storyboard,
+            false => PreviewKind::Static,
+        }
+    }
+}
+
+/// Video file, either downloadable or part of streaming playlist.
+#[derive(Debug, Clone)]
+pub struct VideoFile<'a> {
+    /// Link to file.
+    pub url: &'a url::Url,
+
+    /// Media type of file, e.g. `video/mp4`.
+    pub media_type: &'a str,
+
+    /// Vertical resolution, e.g. 720.
+    pub height: Option<u32>,
+
+    /// Size of file in bytes.
+    pub size: Option<u64>,
+
+    /// Frame rate.
+    pub fps: Option<f64>,
+
+    /// `true` if file is fragment of streaming playlist.
+    pub is_streaming: bool,
+}
+
+impl<'a> VideoFile<'a> {
+    fn from_link(link: &'a Link, is_streaming: bool) -> Option<Self> {
+        let media_type = link.media_type.as_deref()?;
+
+        let is_media = media_type.starts_with("video/") ||
+            media_type.starts_with("audio/");
+
+        if !is_media {
+            return None;
+        }
+
+        Some(Self {
+            url: &link.href,
+            media_type,
+            height: link.height,
+            size: link.size,
+            fps: link.fps,
+            is_streaming,
+        })
+    }
+}
+
+/// Typed view of PeerTube `Video` object.
+///
+/// PeerTube specific properties are part of [Content],
+/// this view interprets them along with `url` links.
+#[derive(Debug, Clone)]
+pub struct Video<'a> {
+    /// Video itself.
+    pub content: &'a Content,
+}
+
+impl<'a> Video<'a> {
+    /// Returns duration of video, if valid.
+    pub fn duration(&self) -> Option<Duration> {
+        self.content.duration.as_ref()
+            .and_then(|duration| duration.to_duration())
+    }
+
+    /// Returns number of views, if reported.
+    pub fn views(&self) -> Option<u64> {
+        self.content.views
+    }
+
+    /// Returns category of video.
+    pub fn category(&self) -> Option<&'a VideoLabel> {
+        self.content.category.as_ref()
+    }
+
+    /// Returns licence of video.
+    pub fn licence(&self) -> Option<&'a VideoLabel> {
+        self.content.licence.as_ref()
+    }
+
+    /// Returns language of video.
+    pub fn language(&self) -> Option<&'a VideoLabel> {
+        self.content.language.as_ref()
+    }
+
+    /// Returns subtitles available for video.
+    pub fn subtitles(&self) -> &'a [Subtitle] {
+        self.content.subtitle_language.as_deref()
+            .unwrap_or_default()
+    }
+
+    /// Returns [Link] objects of `url` property.
+    fn links(&self) -> Vec<&'a Link> {
+        self.content.object_entity.url.iter()
+            .flat_map(|reference| reference.links())
+            .collect()
+    }
+
+    /// Returns HTML page to watch video.
+    pub fn watch_url(&self) -> Option<&'a url::Url> {
+        self.content.object_entity.url.as_ref()?
+            .find_by_media_type("text/html")
+            .map(|link| &link.href)
+    }
+
+    /// Returns HLS streaming playlist links.
+    pub fn streaming_playlists(&self) -> Vec<&'a Link> {
+        self.links()
+            .into_iter()
+            .filter(|link| link.has_media_type(HLS_MEDIA_TYPE))
+            .collect()
+    }
+
+    /// Returns video files ordered from the highest resolution to the lowest.
+    /// Files of streaming playlists are included.
+    pub fn files(&self) -> Vec<VideoFile<'a>> {
+        let links = self.links();
+
+        let mut files: Vec<VideoFile> = links.iter()
+            .filter_map(|link| VideoFile::from_link(link, false))
+            .chain(links.iter()
+                .filter(|link| link.has_media_type(HLS_MEDIA_TYPE))
+                .flat_map(|link| link.nested_links())
+                .filter_map(|link| VideoFile::from_link(link, true))
+            )
+            .collect();
+
+        // sort is stable, downloadable files stay ahead of fragments.
+        files.sort_by_key(|file| std::cmp::Reverse(file.height));
+        files
+    }
+
+    /// Returns video file of the highest resolution not exceeding
+    /// `max_height`. Downloadable files are preferred over fragments
+    /// of streaming playlist.
+    pub fn best_file(&self, max_height: u32) -> Option<VideoFile<'a>> {
+        self.files()
+            .into_iter()
+            .filter(|file| file.height.map(|height| height <= max_height).unwrap_or(true))
+            .max_by_key(|file| (file.height, !file.is_streaming))
+    }
+
+    /// Returns previews of given `kind`.
+    pub fn previews(&self, kind: PreviewKind) -> Vec<&'a VideoPreview> {
+        self.content.preview.iter()
+            .flatten()
+            .filter(|preview| preview.kind() == kind)
+            .collect()
+    }
+
+    /// Returns the first storyboard image along with its geometry.
+    pub fn storyboard(&self) -> Option<(&'a PreviewImage, StoryboardGeometry)> {
+        self.previews(PreviewKind::Storyboard)
+            .into_iter()
+            .flat_map(|preview| preview.url.iter())
+            .find_map(|image| image.storyboard_geometry()
+                .map(|geometry| (image, geometry))
+            )
+    }
+}
+
+impl Content {
+    /// Returns typed [Video] view if this content is `Video`.
+    pub fn video(&self) -> Option<Video<'_>> {
+        match self.entity_type() {
+            EntityType::Video => Some(Video { content: self }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::content::Content;
+    use crate::video::{PreviewImage, PreviewKind, StoryboardGeometry, Tile};
+
+    const PEERTUBE_VIDEO: &str = r#"{
+      "type": "Video",
+      "id": "https://peertube.stream/videos/watch/xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx",
+      "name": "Cats in boxes",
+      "duration": "PT92S",
+      "uuid": "xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx",
+      "category": {"identifier": "15", "name": "Science & Technology"},
+      "licence": {"identifier": "1", "name": "Attribution"},
+      "language": {"identifier": "en", "name": "English"},
+      "views": 42,
+      "sensitive": false,
+      "published": "2024-01-01T00:00:00.000Z",
+      "mediaType": "text/markdown",
+      "content": "Cats",
+      "subtitleLanguage": [
+        {
+          "identifier": "fr",
+          "name": "French",
+          "url": "https://peertube.stream/lazy-static/video-captions/xxx-fr.vtt"
+        }
+      ],
+      "icon": [
+        {
+          "type": "Image",
+          "url": "https://peertube.stream/lazy-static/thumbnails/xxx.jpg",
+          "mediaType": "image/jpeg",
+          "width": 280,
+          "height": 157
+        }
+      ],
+      "preview": [
+        {
+          "type": "Image",
+          "rel": ["storyboard"],
+          "url": [
+            {
+              "mediaType": "image/jpeg",
+              "href": "https://peertube.stream/lazy-static/storyboards/xyz.jpg",
+              "width": 1920,
+              "height": 1080,
+              "tileWidth": 192,
+              "tileHeight": 108,
+              "tileDuration": "PT1S"
+            }
+          ]
+        }
+      ],
+      "url": [
+        {
+          "type": "Link",
+          "mediaType": "text/html",
+          "href": "https://peertube.stream/w/abc"
+        },
+        {
+          "type": "Link",
+          "mediaType": "application/x-mpegURL",
+          "href": "https://peertube.stream/static/streaming-playlists/hls/yyy/master.m3u8",
+          "tag": [
+            {"type": "Infohash", "name": "0123456789abcdef"},
+            {
+              "type": "Link",
+              "mediaType": "video/mp4",
+              "href": "https://peertube.stream/static/streaming-playlists/hls/yyy/zzz-1080-fragmented.mp4",
+              "height": 1080,
+              "size": 2000000,
+              "fps": 30
+            },
+            {
+              "type": "Link",
+              "mediaType": "video/mp4",
+              "href": "https://peertube.stream/static/streaming-playlists/hls/yyy/zzz-480-fragmented.mp4",
+              "height": 480,
+              "size": 500000,
+              "fps": 30
+            }
+          ]
+        },
+        {
+          "type": "Link",
+          "mediaType": "video/mp4",
+          "href": "https://peertube.stream/static/web-videos/zzz-480.mp4",
+          "height": 480,
+          "size": 600000,
+          "fps": 30
+        }
+      ],
+      "attributedTo": "https://peertube.stream/accounts/xyz",
+      "to": ["https://www.w3.org/ns/activitystreams#Public"]
+    }"#;
+
+    #[test]
+    fn test_peertube_video() {
+        let content: Content = serde_json::from_str(PEERTUBE_VIDEO).unwrap();
+        let video = content.video().unwrap();
+
+        assert_eq!(Some(Duration::from_secs(92)), video.duration());
+        assert_eq!(Some(42), video.views());
+        assert_eq!("15", video.category().unwrap().identifier);
+        assert_eq!("en", video.language().unwrap().identifier);
+        assert_eq!("fr", video.subtitles()[0].identifier);
+        assert_eq!("https://peertube.stream/w/abc", video.watch_url().unwrap().as_str());
+        assert_eq!(1, video.streaming_playlists().len());
+
+        let files = video.files();
+
+        assert_eq!(3, files.len());
+        assert_eq!(Some(1080), files[0].height);
+        assert!(files[0].is_streaming);
+
+        let best = video.best_file(720).unwrap();
+
+        assert_eq!(Some(480), best.height);
+        assert!(!best.is_streaming);
+
+        assert!(video.previews(PreviewKind::Static).is_empty());
+
+        let (_, geometry) = video.storyboard().unwrap();
+
+        assert_eq!(100, geometry.tiles());
+
+        assert_eq!(
+            Some(Tile { x: 384, y: 108, width: 192, height: 108 }),
+            geometry.tile_at(Duration::from_millis(12_500))
+        );
+
+        assert_eq!(
+            Some(Tile { x: 1728, y: 972, width: 192, height: 108 }),
+            geometry.tile_at(Duration::from_secs(1000))
+        );
+    }
+
+    #[test]
+    fn test_unexpected_video_properties_do_not_fail_content() {
+        let content: Content = serde_json::from_str(r#"{
+          "type": "Video",
+          "id": "https://z.y.x/videos/1",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "duration": "forever",
+          "language": "en",
+          "views": "many"
+        }"#).unwrap();
+
+        let video = content.video().unwrap();
+
+        assert_eq!(None, video.duration());
+        assert!(video.language().is_none());
+        assert_eq!(None, video.views());
+        assert!(video.files().is_empty());
+    }
+
+    #[test]
+    fn test_storyboard_geometry() {
+        let image: PreviewImage = serde_json::from_str(r#"{
+          "href": "https://z.y.x/storyboards/1.jpg",
+          "width": 300,
+          "height": 100,
+          "tileWidth": 100,
+          "tileHeight": 100,
+          "tileDuration": "PT2S"
+        }"#).unwrap();
+
+        let geometry = image.storyboard_geometry().unwrap();
+
+        assert_eq!((3, 1), (geometry.columns, geometry.rows));
+        assert_eq!(Some(Tile { x: 0, y: 0, width: 100, height: 100 }), geometry.tile_at(Duration::ZERO));
+        assert_eq!(Some(200), geometry.tile_at(Duration::from_secs(5)).map(|tile| tile.x));
+        assert_eq!(Some(200), geometry.tile_at(Duration::MAX).map(|tile| tile.x));
+
+        let thumbnail: PreviewImage = serde_json::from_str(r#"{
+          "href": "https://z.y.x/thumbnails/1.jpg",
+          "width": 300,
+          "height": 100,
+          "tileWidth": 0,
+          "tileHeight": 100,
+          "tileDuration": "PT2S"
+        }"#).unwrap();
+
+        assert!(thumbnail.storyboard_geometry().is_none());
+    }
+
+    #[test]
+    fn test_storyboard_geometry_edge_cases() {
+        let huge = StoryboardGeometry {
+            columns: u32::MAX,
+            rows: u32::MAX,
+            tile_width: u32::MAX,
+            tile_height: u32::MAX,
+            tile_duration: Duration::from_nanos(1),
+        };
+
+        assert_eq!(u32::MAX, huge.tiles());
+        assert!(huge.tile_at(Duration::from_secs(u64::MAX)).is_some());
+
+        let empty = StoryboardGeometry {
+            columns: 0,
+            ..huge
+        };
+
+        assert_eq!(0, empty.tiles());
+        assert_eq!(None, empty.tile_at(Duration::from_secs(1)));
+
+        let still = StoryboardGeometry {
+            columns: 2,
+            rows: 2,
+            tile_width: 10,
+            tile_height: 10,
+            tile_duration: Duration::ZERO,
+        };
+
+        assert_eq!(Some(0), still.tile_at(Duration::from_secs(60)).map(|tile| tile.x));
+    }
+
+    #[test]
+    fn test_static_preview_and_missing_files() {
+        let content: Content = serde_json::from_str(r#"{
+          "type": "Video",
+          "id": "https://z.y.x/videos/2",
+          "attributedTo": "https://z.y.x/users/xyz",
+          "published": "2024-01-01T00:00:00Z",
+          "preview": [
+            {
+              "type": "Image",
+              "url": [{"href": "https://z.y.x/thumbnails/2.jpg", "mediaType": "image/jpeg"}]
+            }
+          ],
+          "url": "https://z.y.x/w/2"
+        }"#).unwrap();
+
+        let video = content.video().unwrap();
+
+        assert_eq!(1, video.previews(PreviewKind::Static).len());
+        assert!(video.storyboard().is_none());
+        assert!(video.files().is_empty());
+        assert!(video.best_file(1080).is_none());
+        assert!(video.watch_url().is_none());
+    }
+}